@@ -0,0 +1,228 @@
+use anyhow::Result;
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextParseQuery, NextValidation,
+};
+use async_graphql::parser::types::{ExecutableDocument, Selection};
+use async_graphql::{
+    Error as GraphQLError, ServerError, ServerResult, ValidationResult, Variables,
+};
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::app::api::security::auth::AccessClaims;
+use crate::app::core::error::{CustomError, CustomErrorKind::QueryTooComplex};
+use crate::model::profile::profile_model::Permission;
+
+/// Стоимость поля, которое выполняет запрос к Neo4j
+pub const DB_COST: usize = 5;
+/// Предполагаемое количество элементов в списке без пагинации
+pub const LIST_SIZE: usize = 10;
+/// Допустимая глубина запросов интроспекции, в нее укладывается
+/// стандартный запрос GraphiQL с фрагментом `TypeRef`
+pub const INTROSPECTION_DEPTH: usize = 15;
+
+/// Размер страницы списка: `first` элементов, по умолчанию `default`,
+/// не более `max`. Используется и для выборки, и для расчета сложности запроса.
+pub fn page_size(first: Option<i32>, default: usize, max: usize) -> usize {
+    first
+        .map_or(default, |first| first.max(0) as usize)
+        .min(max)
+}
+
+/// Отбрасывание лишней записи, выбранной сверх `limit`.
+///
+/// Страница выбирается с одной лишней записью, ее наличие
+/// показывает, есть ли следующая страница.
+pub fn truncate_page<T>(items: &mut Vec<T>, limit: usize) -> bool {
+    let has_next_page = items.len() > limit;
+    items.truncate(limit);
+    has_next_page
+}
+
+lazy_static! {
+    /// Бюджеты запросов из раздела `query_budgets` файла `LIMITS_PATH`.
+    /// Пустой путь или отсутствующие в файле значения заменяются значениями по умолчанию.
+    pub static ref QUERY_BUDGETS: QueryBudgets =
+        load_budgets(dotenv!("LIMITS_PATH")).expect("Failed to load query budgets");
+}
+
+/// Допустимые глубина и сложность запроса
+#[derive(Deserialize)]
+struct QueryBudget {
+    depth: usize,
+    complexity: usize,
+}
+
+/// Бюджеты запросов по ролям
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct QueryBudgets {
+    /// Бюджет анонимных клиентов
+    guest: QueryBudget,
+    user: QueryBudget,
+    developer: QueryBudget,
+    admin: QueryBudget,
+}
+
+impl Default for QueryBudgets {
+    fn default() -> Self {
+        Self {
+            guest: QueryBudget {
+                depth: 6,
+                complexity: 100,
+            },
+            user: QueryBudget {
+                depth: 8,
+                complexity: 300,
+            },
+            developer: QueryBudget {
+                depth: 12,
+                complexity: 1000,
+            },
+            admin: QueryBudget {
+                depth: 12,
+                complexity: 1000,
+            },
+        }
+    }
+}
+
+impl QueryBudgets {
+    fn budget(&self, permission: Permission) -> &QueryBudget {
+        match permission {
+            Permission::Guest => &self.guest,
+            Permission::User => &self.user,
+            Permission::Developer => &self.developer,
+            Permission::Admin => &self.admin,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LimitsFile {
+    #[serde(default)]
+    query_budgets: QueryBudgets,
+}
+
+fn load_budgets(path: &str) -> Result<QueryBudgets> {
+    if path.is_empty() {
+        return Ok(QueryBudgets::default());
+    }
+
+    let file: LimitsFile = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+
+    Ok(file.query_budgets)
+}
+
+/// Ограничение глубины и сложности запроса в зависимости от роли.
+///
+/// Сложность считается по аннотациям `complexity` полей схемы:
+/// поля, обращающиеся к базе, стоят `DB_COST`, списки умножают
+/// стоимость вложенных полей на количество элементов.
+///
+/// Запросы интроспекции не ограничиваются по сложности, иначе схему
+/// нельзя было бы получить без авторизации, но их глубина не превышает
+/// `INTROSPECTION_DEPTH`.
+pub struct QueryLimits;
+
+impl ExtensionFactory for QueryLimits {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(QueryLimitsExtension {
+            introspection: AtomicBool::new(false),
+        })
+    }
+}
+
+struct QueryLimitsExtension {
+    introspection: AtomicBool,
+}
+
+#[async_trait]
+impl Extension for QueryLimitsExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let doc = next.run(ctx, query, variables).await?;
+        self.introspection
+            .store(is_introspection(&doc), Ordering::Relaxed);
+
+        Ok(doc)
+    }
+
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> Result<ValidationResult, Vec<ServerError>> {
+        let result = next.run(ctx).await?;
+
+        if self.introspection.load(Ordering::Relaxed) {
+            if result.depth > INTROSPECTION_DEPTH {
+                return Err(vec![too_complex(&format!(
+                    "Introspection query depth {} exceeds the limit of {}",
+                    result.depth, INTROSPECTION_DEPTH
+                ))]);
+            }
+
+            return Ok(result);
+        }
+
+        let permission = ctx
+            .data_opt::<Result<Option<AccessClaims>, CustomError>>()
+            .and_then(|claims| claims.as_ref().ok())
+            .and_then(|claims| claims.as_ref())
+            .map_or(Permission::Guest, |claims| claims.permission());
+        let budget = QUERY_BUDGETS.budget(permission);
+
+        if result.depth > budget.depth {
+            return Err(vec![too_complex(&format!(
+                "Query depth {} exceeds the limit of {}",
+                result.depth, budget.depth
+            ))]);
+        }
+
+        if result.complexity > budget.complexity {
+            return Err(vec![too_complex(&format!(
+                "Query complexity {} exceeds the limit of {}",
+                result.complexity, budget.complexity
+            ))]);
+        }
+
+        Ok(result)
+    }
+}
+
+/// Запрос содержит только служебные поля `__schema` и `__type`
+fn is_introspection(doc: &ExecutableDocument) -> bool {
+    doc.operations.iter().all(|(_, operation)| {
+        operation
+            .node
+            .selection_set
+            .node
+            .items
+            .iter()
+            .all(|selection| match &selection.node {
+                Selection::Field(field) => field.node.name.node.starts_with("__"),
+                _ => false,
+            })
+    })
+}
+
+fn too_complex(details: &str) -> ServerError {
+    let err = GraphQLError::from(
+        CustomError::new()
+            .kind(QueryTooComplex)
+            .details(details)
+            .build(),
+    );
+
+    ServerError {
+        extensions: err.extensions,
+        ..ServerError::new(err.message, None)
+    }
+}
@@ -1,22 +1,66 @@
+pub mod limits;
+
 use async_graphql::{EmptySubscription, MergedObject, Schema};
 
 use crate::{
+    app::api::security::rate_limit::RateLimit,
     app::core::context::Context,
+    model::admin::admin_resolver::AdminMutation,
+    model::api_key::api_key_resolver::{ApiKeyMutation, ApiKeyQuery},
+    model::audit::audit_resolver::AuditQuery,
+    model::identity::identity_resolver::IdentityMutation,
+    model::partner::partner_resolver::PartnerQuery,
     model::profile::profile_resolver::{ProfileMutation, ProfileQuery},
+    model::session::session_resolver::{SessionMutation, SessionQuery},
+    model::suggestion::suggestion_resolver::{SuggestionMutation, SuggestionQuery},
+    model::token::token_resolver::TokenMutation,
+    model::two_factor::two_factor_resolver::TwoFactorMutation,
 };
 
 #[derive(MergedObject, Default)]
-pub struct Query(ProfileQuery);
+pub struct Query(
+    ProfileQuery,
+    SessionQuery,
+    ApiKeyQuery,
+    AuditQuery,
+    PartnerQuery,
+    SuggestionQuery,
+);
 
 #[derive(MergedObject, Default)]
-pub struct Mutation(ProfileMutation);
+pub struct Mutation(
+    ProfileMutation,
+    TokenMutation,
+    SessionMutation,
+    AdminMutation,
+    TwoFactorMutation,
+    IdentityMutation,
+    ApiKeyMutation,
+    SuggestionMutation,
+);
 
 pub type AppSchema = Schema<Query, Mutation, EmptySubscription>;
 
 pub fn build_schema_with_context(ctx: Context) -> AppSchema {
     Schema::build(Query::default(), Mutation::default(), EmptySubscription)
         .data(ctx.profile_service)
+        .data(ctx.token_service)
+        .data(ctx.session_service)
+        .data(ctx.two_factor_service)
+        .data(ctx.identity_service)
+        .data(ctx.api_key_service)
+        .data(ctx.audit_service)
+        .data(ctx.partner_service)
+        .data(ctx.suggestion_service)
+        .data(ctx.login_lockout)
+        .data(ctx.proof_of_work)
+        .data(ctx.revocation_store)
+        .data(ctx.suspension_cache)
+        .data(ctx.mailer)
+        .data(ctx.oidc_client)
         .data(ctx.neodb)
+        .extension(RateLimit::new(ctx.rate_limiter))
+        .extension(limits::QueryLimits)
         .enable_subscription_in_federation()
         .finish()
 }
@@ -1,18 +1,56 @@
-use actix_web::HttpRequest;
+use actix_web::{http::header::ToStrError, HttpRequest};
 use anyhow::Result;
 use async_graphql::{Context, Guard, Result as GraphQLResult};
 use chrono::{Duration, Timelike, Utc};
-use jsonwebtoken::{DecodingKey, EncodingKey, Validation};
+use jsonwebtoken::Validation;
 use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
 
-use crate::app::core::error::CustomError;
-use crate::model::profile::profile_model::Permission;
+use super::{keys::KEY_STORE, scope::Scope};
+use crate::app::core::{context::Context as AppContext, error::CustomError};
+use crate::model::api_key::api_key_model::hash_key;
+use crate::model::profile::{
+    profile_error::ERR_PROF__EMAIL_NOT_VERIFIED, profile_model::Permission,
+    profile_repository::ProfileRepositoryT,
+};
+use std::sync::Arc;
 
-pub struct AuthGuard(Permission);
+/// Ограничение доступа по роли или области доступа Access токена
+pub enum AuthGuard {
+    /// Роль не ниже указанной
+    AtLeast(Permission),
+    /// Наличие области доступа
+    Scope(Scope),
+    /// Запрос с токеном сессии, API ключи не принимаются
+    Session,
+    /// Запрос с токеном сессии либо с API ключом, имеющим область доступа
+    SessionOr(Scope),
+}
 
 impl AuthGuard {
-    pub fn new(p: Permission) -> Self {
-        Self(p)
+    pub fn at_least(p: Permission) -> Self {
+        Self::AtLeast(p)
+    }
+
+    pub fn scope(s: Scope) -> Self {
+        Self::Scope(s)
+    }
+
+    pub fn session() -> Self {
+        Self::Session
+    }
+
+    pub fn session_or(s: Scope) -> Self {
+        Self::SessionOr(s)
+    }
+
+    fn allows(&self, claims: &AccessClaims) -> bool {
+        match self {
+            Self::AtLeast(permission) => claims.permission.includes(*permission),
+            Self::Scope(scope) => claims.has_scope(*scope),
+            Self::Session => !claims.is_api_key(),
+            Self::SessionOr(scope) => !claims.is_api_key() || claims.has_scope(*scope),
+        }
     }
 }
 
@@ -24,7 +62,7 @@ impl Guard for AuthGuard {
         match ctx.data_opt::<Result<Option<AccessClaims>, CustomError>>() {
             Some(result) => match result {
                 Ok(data) => match data {
-                    Some(acsess_claims) if acsess_claims.permission == self.0 => Ok(()),
+                    Some(acsess_claims) if self.allows(acsess_claims) => Ok(()),
                     _ => Err(CustomError::new().kind(Forbidden).build().into()),
                 },
 
@@ -40,43 +78,209 @@ impl Guard for AuthGuard {
     }
 }
 
+/// Ограничение доступа для профилей с неподтвержденной почтой.
+///
+/// Используется совместно с `AuthGuard`, который проверяет наличие токена.
+pub struct EmailVerifiedGuard;
+
+#[async_trait::async_trait]
+impl Guard for EmailVerifiedGuard {
+    async fn check(&self, ctx: &Context<'_>) -> GraphQLResult<()> {
+        use crate::app::core::error::CustomErrorKind::Forbidden;
+
+        let claims = match ctx.data_opt::<Result<Option<AccessClaims>, CustomError>>() {
+            Some(Ok(Some(claims))) => claims,
+            _ => return Err(CustomError::new().kind(Forbidden).build().into()),
+        };
+
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let profile = profile_service.get_data(claims.sub().to_string()).await?;
+
+        if profile.email_verified {
+            Ok(())
+        } else {
+            Err(CustomError::new()
+                .kind(Forbidden)
+                .details(&ERR_PROF__EMAIL_NOT_VERIFIED)
+                .build()
+                .into())
+        }
+    }
+}
+
 fn split_token(header_value: &str) -> Vec<&str> {
     let split = header_value.split(" ");
     split.collect::<Vec<&str>>()
 }
 
-pub fn parse_auth<'a>(http_request: HttpRequest) -> Result<Option<AccessClaims>, CustomError<'a>> {
-    match http_request.headers().get("Authorization") {
-        Some(token) if split_token(token.to_str()?).len() == 2 => {
-            let claims = Token::<AccessClaims>::decode(split_token(token.to_str()?)[1])?;
+/// Получение полезной нагрузки Access токена из заголовка `Authorization`
+/// либо API ключа из заголовка `X-Api-Key`.
+///
+/// Отозванный токен считается недействительным,
+/// токен заблокированного профиля отклоняется.
+pub async fn parse_auth<'a>(
+    app_ctx: &AppContext,
+    http_request: &HttpRequest,
+) -> Result<Option<AccessClaims>, CustomError<'a>> {
+    authenticate(
+        app_ctx,
+        header_value(http_request, "Authorization")?,
+        header_value(http_request, "X-Api-Key")?,
+    )
+    .await
+}
+
+fn header_value<'r>(
+    http_request: &'r HttpRequest,
+    name: &str,
+) -> Result<Option<&'r str>, ToStrError> {
+    http_request
+        .headers()
+        .get(name)
+        .map(|value| value.to_str())
+        .transpose()
+}
+
+/// Аутентификация по значениям `Authorization` и `X-Api-Key`.
+///
+/// Используется как для HTTP запросов, так и для WebSocket
+/// соединений, где значения передаются в `connection_init`.
+pub async fn authenticate<'a>(
+    app_ctx: &AppContext,
+    authorization: Option<&str>,
+    api_key: Option<&str>,
+) -> Result<Option<AccessClaims>, CustomError<'a>> {
+    let claims = match authorization {
+        Some(token) if split_token(token).len() == 2 => {
+            let claims = Token::<AccessClaims>::decode(split_token(token)[1])?;
+
+            let revoked = app_ctx
+                .revocation_store
+                .is_revoked(&claims)
+                .await
+                .map_err(|err| crate::internal!(&err.details().unwrap_or_default()))?;
+
+            if revoked {
+                use crate::app::core::error::CustomErrorKind::TokenInvalid;
+
+                return Err(CustomError::new()
+                    .kind(TokenInvalid)
+                    .details("Token has been revoked")
+                    .build());
+            }
 
-            Ok(Some(claims))
+            claims
         }
 
         Some(_) => {
             use crate::app::core::error::CustomErrorKind::TokenMissing;
 
-            Err(CustomError::new()
+            return Err(CustomError::new()
                 .kind(TokenMissing)
                 .details("Faild to parse token")
-                .build())
+                .build());
         }
 
-        None => Ok(None),
+        None => match api_key {
+            Some(key) => parse_api_key(app_ctx, key).await?,
+            None => return Ok(None),
+        },
+    };
+
+    let suspension = app_ctx
+        .suspension_cache
+        .get(app_ctx.profile_service.as_ref(), claims.sub())
+        .await?;
+
+    if let Some(suspension) = suspension {
+        return Err(suspension.error());
+    }
+
+    Ok(Some(claims))
+}
+
+/// Получение полезной нагрузки по API ключу.
+///
+/// Роль берется у владельца ключа на момент запроса,
+/// поэтому понижение роли сразу сужает области ключа.
+async fn parse_api_key<'a>(
+    app_ctx: &AppContext,
+    key: &str,
+) -> Result<AccessClaims, CustomError<'a>> {
+    use crate::app::core::error::CustomErrorKind::TokenInvalid;
+
+    let found = app_ctx
+        .api_key_service
+        .authenticate(hash_key(key))
+        .await
+        .map_err(|err| crate::internal!(&err.details().unwrap_or_default()))?;
+
+    match found {
+        Some((api_key, profile_id, permission)) => Ok(AccessClaims::from_api_key(
+            profile_id,
+            permission,
+            &api_key.scopes,
+            api_key.id.to_string(),
+        )),
+
+        None => Err(CustomError::new()
+            .kind(TokenInvalid)
+            .details("Unknown API key")
+            .build()),
     }
 }
 
+/// Получение полезной нагрузки Access токена.
+///
+/// Вызывается только в резолверах, защищенных `AuthGuard`,
+/// который гарантирует наличие токена в контексте.
+pub fn get_access_claims<'a>(ctx: &'a Context<'_>) -> &'a AccessClaims {
+    ctx.data_opt::<Result<Option<AccessClaims>, CustomError>>()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+}
+
 /* JWT */
 
+/// Источник полезной нагрузки Access токена
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ClaimsKind {
+    /// Токен сессии, выпущенный при входе
+    #[default]
+    Session,
+    /// Запрос с API ключом
+    ApiKey,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AccessClaims {
     sub: String,
     exp: i64,
     permission: Permission,
+    /// Области доступа токена
+    #[serde(default)]
+    scopes: Vec<Scope>,
+    /// Уникальный идентификатор токена
+    jti: String,
+    /// Идентификатор сессии, в рамках которой выпущен токен
+    sid: String,
+    /// Поколение токенов профиля на момент выпуска
+    gen: i64,
+    #[serde(default)]
+    kind: ClaimsKind,
 }
 
 impl AccessClaims {
-    pub fn new(sub: String, permission: Permission, d: Duration) -> AccessClaims {
+    pub fn new(
+        sub: String,
+        permission: Permission,
+        sid: String,
+        gen: i64,
+        d: Duration,
+    ) -> AccessClaims {
         // Определение скрока пригодности токена
         let exp = Utc::now() + d;
 
@@ -89,11 +293,78 @@ impl AccessClaims {
             sub,
             exp: exp.timestamp(),
             permission,
+            scopes: Scope::for_permission(permission),
+            jti: Uuid::new_v4().to_string(),
+            sid,
+            gen,
+            kind: ClaimsKind::Session,
+        }
+    }
+
+    /// Полезная нагрузка для запроса с API ключом.
+    ///
+    /// Области ключа ограничиваются областями текущей роли владельца.
+    /// Идентификатор ключа используется как `jti` и `sid`, срок
+    /// действия ключа не ограничен.
+    pub fn from_api_key(
+        sub: String,
+        permission: Permission,
+        scopes: &[Scope],
+        key_id: String,
+    ) -> AccessClaims {
+        let allowed = Scope::for_permission(permission);
+
+        AccessClaims {
+            sub,
+            exp: i64::MAX,
+            permission,
+            scopes: scopes
+                .iter()
+                .filter(|scope| allowed.contains(scope))
+                .copied()
+                .collect(),
+            jti: key_id.clone(),
+            sid: key_id,
+            gen: 0,
+            kind: ClaimsKind::ApiKey,
         }
     }
 
     pub fn sub(&self) -> &str {
-       &self.sub
+        &self.sub
+    }
+
+    pub fn exp(&self) -> i64 {
+        self.exp
+    }
+
+    pub fn permission(&self) -> Permission {
+        self.permission
+    }
+
+    pub fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// Полезная нагрузка получена по API ключу
+    pub fn is_api_key(&self) -> bool {
+        self.kind == ClaimsKind::ApiKey
+    }
+
+    pub fn jti(&self) -> &str {
+        &self.jti
+    }
+
+    pub fn sid(&self) -> &str {
+        &self.sid
+    }
+
+    pub fn gen(&self) -> i64 {
+        self.gen
     }
 }
 
@@ -101,10 +372,15 @@ impl AccessClaims {
 pub struct RefreshClaims {
     sub: String,
     exp: i64,
+    /// Уникальный идентификатор токена
+    jti: String,
+    /// Идентификатор семейства токенов, полученных
+    /// последовательной ротацией от одного входа
+    family: String,
 }
 
 impl RefreshClaims {
-    pub fn new(sub: String, d: Duration) -> Self {
+    pub fn new(sub: String, family: String, d: Duration) -> Self {
         // Определение скрока пригодности токена
         let exp = Utc::now() + d;
 
@@ -116,8 +392,92 @@ impl RefreshClaims {
         Self {
             sub,
             exp: exp.timestamp(),
+            jti: Uuid::new_v4().to_string(),
+            family,
         }
     }
+
+    pub fn sub(&self) -> &str {
+        &self.sub
+    }
+
+    pub fn exp(&self) -> i64 {
+        self.exp
+    }
+
+    pub fn jti(&self) -> &str {
+        &self.jti
+    }
+
+    pub fn family(&self) -> &str {
+        &self.family
+    }
+}
+
+/// Назначение токена действия
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TokenPurpose {
+    VerifyEmail,
+    ResetPassword,
+    TwoFactorLogin,
+    OidcLogin,
+    RegistrationChallenge,
+}
+
+/// Полезная нагрузка одноразовых токенов действий,
+/// например ссылки подтверждения почты.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActionClaims {
+    sub: String,
+    exp: i64,
+    purpose: TokenPurpose,
+    /// Данные, к которым привязан токен, например адрес почты
+    data: Option<String>,
+}
+
+impl ActionClaims {
+    pub fn new(sub: String, purpose: TokenPurpose, data: Option<String>, d: Duration) -> Self {
+        // Определение скрока пригодности токена
+        let exp = Utc::now() + d;
+
+        // Нормализация к временным меткам UNIX
+        let exp = exp
+            .date()
+            .and_hms_milli(exp.hour(), exp.minute(), exp.second(), 0);
+
+        Self {
+            sub,
+            exp: exp.timestamp(),
+            purpose,
+            data,
+        }
+    }
+
+    /// Проверка токена действия.
+    /// Токен, выпущенный для другого действия, недействителен.
+    pub fn decode<'a>(token: &str, purpose: TokenPurpose) -> Result<Self, CustomError<'a>> {
+        let claims = Token::<ActionClaims>::decode(token)?;
+
+        if claims.purpose != purpose {
+            use crate::app::core::error::CustomErrorKind::TokenInvalid;
+
+            return Err(CustomError::new().kind(TokenInvalid).build());
+        }
+
+        Ok(claims)
+    }
+
+    pub fn sub(&self) -> &str {
+        &self.sub
+    }
+
+    pub fn exp(&self) -> i64 {
+        self.exp
+    }
+
+    pub fn data(&self) -> Option<&str> {
+        self.data.as_deref()
+    }
 }
 
 #[derive(Debug)]
@@ -129,22 +489,25 @@ impl<C> Token<C>
 where
     C: Serialize + DeserializeOwned,
 {
+    /// Подпись токена активным ключом.
+    /// Идентификатор ключа передается в заголовке `kid`.
     pub fn encode(claims: C) -> Result<String> {
-        let token = jsonwebtoken::encode(
-            &jsonwebtoken::Header::default(),
-            &claims,
-            &EncodingKey::from_secret("secret_key".as_bytes()),
-        )?;
+        let key = KEY_STORE.active();
+
+        let mut header = jsonwebtoken::Header::new(key.alg());
+        header.kid = Some(key.kid().to_string());
+
+        let token = jsonwebtoken::encode(&header, &claims, key.encoding())?;
 
         Ok(token)
     }
 
+    /// Проверка токена ключом, указанным в заголовке `kid`
     pub fn decode(token: &str) -> Result<C, jsonwebtoken::errors::Error> {
-        match jsonwebtoken::decode::<C>(
-            &token,
-            &DecodingKey::from_secret("secret_key".as_bytes()),
-            &Validation::default(),
-        ) {
+        let header = jsonwebtoken::decode_header(token)?;
+        let key = KEY_STORE.find(header.kid.as_deref())?;
+
+        match jsonwebtoken::decode::<C>(&token, key.decoding(), &Validation::new(key.alg())) {
             Ok(token_data) => Ok(token_data.claims),
             Err(err) => Err(err),
         }
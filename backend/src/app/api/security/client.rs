@@ -0,0 +1,37 @@
+use actix_web::HttpRequest;
+
+/// Сведения о клиенте, выполняющем запрос.
+///
+/// Передаются в контекст каждого GraphQL запроса вместе с
+/// полезной нагрузкой Access токена.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    ip: Option<String>,
+    user_agent: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn from_request(http_request: &HttpRequest) -> Self {
+        Self {
+            // Используется адрес сокета, а не заголовки `Forwarded`,
+            // так как их значения клиент может подделать
+            ip: http_request
+                .connection_info()
+                .peer_addr()
+                .map(|ip| ip.to_string()),
+            user_agent: http_request
+                .headers()
+                .get("User-Agent")
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string()),
+        }
+    }
+
+    pub fn ip(&self) -> Option<&str> {
+        self.ip.as_deref()
+    }
+
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+}
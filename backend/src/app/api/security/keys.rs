@@ -0,0 +1,240 @@
+use anyhow::{anyhow, Result};
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, EncodingKey};
+use ring::signature::{Ed25519KeyPair, KeyPair, RsaKeyPair};
+use std::collections::HashMap;
+
+lazy_static! {
+    /// Набор ключей подписи JWT, загружаемый из файла `JWT_KEYS_PATH`
+    pub static ref KEY_STORE: KeyStore =
+        KeyStore::load(dotenv!("JWT_KEYS_PATH")).expect("Failed to load JWT keys");
+}
+
+/// Описание ключа в конфигурационном файле.
+///
+/// Для HMAC алгоритмов указывается `secret`, для RSA и EdDSA
+/// путь к закрытому ключу в формате PEM.
+#[derive(Deserialize)]
+struct KeyConfig {
+    kid: String,
+    alg: Algorithm,
+    secret: Option<String>,
+    private_key: Option<String>,
+}
+
+/// Конфигурационный файл ключей.
+///
+/// Токены подписываются ключом `active`, остальные ключи
+/// используются только для проверки ранее выпущенных токенов,
+/// пока не истечет их срок действия.
+#[derive(Deserialize)]
+struct KeysConfig {
+    active: String,
+    keys: Vec<KeyConfig>,
+}
+
+/// Открытая часть ключа в формате JWK (RFC 7517)
+#[derive(Debug, Clone, Serialize)]
+pub struct Jwk {
+    kty: &'static str,
+    #[serde(rename = "use")]
+    key_use: &'static str,
+    alg: Algorithm,
+    kid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crv: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    e: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    x: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+pub struct SigningKey {
+    kid: String,
+    alg: Algorithm,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    /// Открытый ключ для публикации, отсутствует у симметричных ключей
+    jwk: Option<Jwk>,
+}
+
+impl SigningKey {
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    pub fn alg(&self) -> Algorithm {
+        self.alg
+    }
+
+    pub fn encoding(&self) -> &EncodingKey {
+        &self.encoding
+    }
+
+    pub fn decoding(&self) -> &DecodingKey {
+        &self.decoding
+    }
+
+    fn from_config(config: KeyConfig) -> Result<Self> {
+        match config.alg {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                let secret = config
+                    .secret
+                    .ok_or_else(|| anyhow!("Key `{}` has no secret", config.kid))?;
+
+                Ok(Self {
+                    kid: config.kid,
+                    alg: config.alg,
+                    encoding: EncodingKey::from_secret(secret.as_bytes()),
+                    decoding: DecodingKey::from_secret(secret.as_bytes()),
+                    jwk: None,
+                })
+            }
+
+            Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 => {
+                let pem_data = read_private_key(&config)?;
+                let pem = pem::parse(&pem_data)?;
+
+                let key_pair = match pem.tag.as_str() {
+                    "RSA PRIVATE KEY" => RsaKeyPair::from_der(&pem.contents),
+                    _ => RsaKeyPair::from_pkcs8(&pem.contents),
+                }
+                .map_err(|err| anyhow!("Key `{}` is rejected: {}", config.kid, err))?;
+
+                let n = key_pair
+                    .public_key()
+                    .modulus()
+                    .big_endian_without_leading_zero()
+                    .to_vec();
+                let e = key_pair
+                    .public_key()
+                    .exponent()
+                    .big_endian_without_leading_zero()
+                    .to_vec();
+
+                Ok(Self {
+                    encoding: EncodingKey::from_rsa_pem(&pem_data)?,
+                    decoding: DecodingKey::from_rsa_raw_components(&n, &e),
+                    jwk: Some(Jwk {
+                        kty: "RSA",
+                        key_use: "sig",
+                        alg: config.alg,
+                        kid: config.kid.clone(),
+                        crv: None,
+                        n: Some(base64_url(&n)),
+                        e: Some(base64_url(&e)),
+                        x: None,
+                    }),
+                    kid: config.kid,
+                    alg: config.alg,
+                })
+            }
+
+            Algorithm::EdDSA => {
+                let pem_data = read_private_key(&config)?;
+                let pem = pem::parse(&pem_data)?;
+
+                let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(&pem.contents)
+                    .map_err(|err| anyhow!("Key `{}` is rejected: {}", config.kid, err))?;
+                let x = key_pair.public_key().as_ref().to_vec();
+
+                Ok(Self {
+                    encoding: EncodingKey::from_ed_pem(&pem_data)?,
+                    decoding: DecodingKey::from_ed_der(&x),
+                    jwk: Some(Jwk {
+                        kty: "OKP",
+                        key_use: "sig",
+                        alg: config.alg,
+                        kid: config.kid.clone(),
+                        crv: Some("Ed25519"),
+                        n: None,
+                        e: None,
+                        x: Some(base64_url(&x)),
+                    }),
+                    kid: config.kid,
+                    alg: config.alg,
+                })
+            }
+
+            alg => Err(anyhow!(
+                "Algorithm `{:?}` of key `{}` is not supported",
+                alg,
+                config.kid
+            )),
+        }
+    }
+}
+
+pub struct KeyStore {
+    active: String,
+    keys: HashMap<String, SigningKey>,
+}
+
+impl KeyStore {
+    pub fn load(path: &str) -> Result<Self> {
+        let config: KeysConfig = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+
+        let mut keys = HashMap::new();
+        for key_config in config.keys {
+            let key = SigningKey::from_config(key_config)?;
+            keys.insert(key.kid.clone(), key);
+        }
+
+        if !keys.contains_key(&config.active) {
+            return Err(anyhow!("Active key `{}` is not configured", config.active));
+        }
+
+        Ok(Self {
+            active: config.active,
+            keys,
+        })
+    }
+
+    /// Ключ, которым подписываются новые токены
+    pub fn active(&self) -> &SigningKey {
+        &self.keys[&self.active]
+    }
+
+    /// Поиск ключа для проверки токена по заголовку `kid`.
+    ///
+    /// Токен без `kid` проверяется активным ключом.
+    pub fn find(&self, kid: Option<&str>) -> Result<&SigningKey, jsonwebtoken::errors::Error> {
+        match kid {
+            Some(kid) => self
+                .keys
+                .get(kid)
+                .ok_or_else(|| ErrorKind::InvalidToken.into()),
+            None => Ok(self.active()),
+        }
+    }
+
+    /// Открытые ключи для публикации по адресу `/.well-known/jwks.json`
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .keys
+                .values()
+                .filter_map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+}
+
+fn read_private_key(config: &KeyConfig) -> Result<Vec<u8>> {
+    let path = config
+        .private_key
+        .as_ref()
+        .ok_or_else(|| anyhow!("Key `{}` has no private key", config.kid))?;
+
+    Ok(std::fs::read(path)?)
+}
+
+fn base64_url(data: &[u8]) -> String {
+    base64::encode_config(data, base64::URL_SAFE_NO_PAD)
+}
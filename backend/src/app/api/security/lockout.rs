@@ -0,0 +1,161 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::app::core::error::{CustomError, CustomErrorKind::AccountLocked};
+
+/// Параметры прогрессивной блокировки
+struct LockoutPolicy {
+    /// Количество неудачных попыток, после которого включается блокировка
+    free_attempts: u32,
+    /// Длительность первой блокировки в секундах.
+    /// Каждая следующая неудачная попытка удваивает её.
+    base_lock: i64,
+    /// Максимальная длительность блокировки в секундах
+    max_lock: i64,
+    /// Время в секундах без неудачных попыток, после которого счетчик сбрасывается
+    reset_after: i64,
+}
+
+const ACCOUNT_POLICY: LockoutPolicy = LockoutPolicy {
+    free_attempts: 5,
+    base_lock: 30,
+    max_lock: 60 * 60,
+    reset_after: 60 * 60,
+};
+
+const IP_POLICY: LockoutPolicy = LockoutPolicy {
+    free_attempts: 20,
+    base_lock: 60,
+    max_lock: 6 * 60 * 60,
+    reset_after: 6 * 60 * 60,
+};
+
+struct Attempts {
+    failures: u32,
+    last_failure: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+/// Счетчик неудачных попыток для одного типа ключа
+struct AttemptCounter {
+    policy: LockoutPolicy,
+    attempts: Mutex<HashMap<String, Attempts>>,
+}
+
+impl AttemptCounter {
+    fn new(policy: LockoutPolicy) -> Self {
+        Self {
+            policy,
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Оставшееся время блокировки в секундах
+    fn locked_for(&self, key: &str) -> Option<i64> {
+        let attempts = self.attempts.lock().unwrap();
+
+        attempts
+            .get(key)
+            .and_then(|item| item.locked_until)
+            .map(|until| (until - Utc::now()).num_seconds())
+            .filter(|seconds| *seconds > 0)
+    }
+
+    fn failure(&self, key: &str) {
+        let now = Utc::now();
+        let mut attempts = self.attempts.lock().unwrap();
+
+        // Удаляю записи, по которым давно не было ошибок
+        attempts.retain(|_, item| {
+            (now - item.last_failure).num_seconds() < self.policy.reset_after
+                || item.locked_until.map_or(false, |until| until > now)
+        });
+
+        let item = attempts.entry(key.to_string()).or_insert(Attempts {
+            failures: 0,
+            last_failure: now,
+            locked_until: None,
+        });
+
+        item.failures += 1;
+        item.last_failure = now;
+
+        if item.failures >= self.policy.free_attempts {
+            let exponent = (item.failures - self.policy.free_attempts).min(16);
+            let lock = (self.policy.base_lock << exponent).min(self.policy.max_lock);
+
+            item.locked_until = Some(now + Duration::seconds(lock));
+        }
+    }
+
+    fn reset(&self, key: &str) {
+        self.attempts.lock().unwrap().remove(key);
+    }
+}
+
+/// Защита метода авторизации от перебора паролей.
+///
+/// Неудачные попытки считаются отдельно для каждого профиля и
+/// для каждого IP адреса. После исчерпания бесплатных попыток
+/// ключ блокируется, и длительность блокировки растет с каждой
+/// новой ошибкой.
+pub struct LoginLockout {
+    accounts: AttemptCounter,
+    ips: AttemptCounter,
+}
+
+impl Default for LoginLockout {
+    fn default() -> Self {
+        Self {
+            accounts: AttemptCounter::new(ACCOUNT_POLICY),
+            ips: AttemptCounter::new(IP_POLICY),
+        }
+    }
+}
+
+impl LoginLockout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn check_ip<'a>(&self, ip: Option<&str>) -> Result<(), CustomError<'a>> {
+        match ip.and_then(|ip| self.ips.locked_for(ip)) {
+            Some(seconds) => Err(locked_error(seconds)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn check_account<'a>(&self, profile_id: &str) -> Result<(), CustomError<'a>> {
+        match self.accounts.locked_for(profile_id) {
+            Some(seconds) => Err(locked_error(seconds)),
+            None => Ok(()),
+        }
+    }
+
+    /// Зафиксировать неудачную попытку входа
+    pub fn failure(&self, profile_id: Option<&str>, ip: Option<&str>) {
+        if let Some(profile_id) = profile_id {
+            self.accounts.failure(profile_id);
+        }
+
+        if let Some(ip) = ip {
+            self.ips.failure(ip);
+        }
+    }
+
+    /// Сбросить счетчик профиля после успешного входа.
+    ///
+    /// Счетчик IP адреса не сбрасывается, иначе перебор по многим
+    /// профилям можно было бы обнулять входом в собственный.
+    pub fn success(&self, profile_id: &str) {
+        self.accounts.reset(profile_id);
+    }
+}
+
+fn locked_error<'a>(seconds: i64) -> CustomError<'a> {
+    CustomError::new()
+        .kind(AccountLocked)
+        .details(&format!("Try again in {} seconds", seconds))
+        .build()
+}
@@ -1 +1,13 @@
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod client;
+pub mod keys;
+pub mod lockout;
+pub mod oidc;
+pub mod password;
+pub mod pow;
+pub mod rate_limit;
+pub mod revocation;
+pub mod scope;
+pub mod subscription;
+pub mod suspension;
+pub mod totp;
@@ -0,0 +1,372 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use rand::Rng;
+use reqwest::Url;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::app::core::error::{CustomError, CustomErrorKind::TokenInvalid};
+
+lazy_static! {
+    /// Провайдеры OpenID Connect из файла `OIDC_PROVIDERS_PATH`.
+    /// Пустой путь отключает вход через внешних провайдеров.
+    pub static ref OIDC_PROVIDERS: HashMap<String, OidcProvider> =
+        load_providers(dotenv!("OIDC_PROVIDERS_PATH")).expect("Failed to load OIDC providers");
+}
+
+/// Время хранения набора ключей провайдера в секундах
+const JWKS_TTL: i64 = 60 * 60;
+
+/// Описание провайдера в конфигурационном файле.
+///
+/// Адреса указываются явно, а не получаются через discovery,
+/// чтобы провайдера можно было подменить локальным mock-сервером.
+#[derive(Deserialize)]
+pub struct OidcProvider {
+    name: String,
+    issuer: String,
+    client_id: String,
+    client_secret: Option<String>,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    redirect_uri: String,
+    #[serde(default = "default_scopes")]
+    scopes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ProvidersConfig {
+    providers: Vec<OidcProvider>,
+}
+
+fn default_scopes() -> Vec<String> {
+    vec!["openid".to_string(), "email".to_string()]
+}
+
+fn load_providers(path: &str) -> Result<HashMap<String, OidcProvider>> {
+    if path.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let config: ProvidersConfig = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+
+    Ok(config
+        .providers
+        .into_iter()
+        .map(|provider| (provider.name.clone(), provider))
+        .collect())
+}
+
+impl OidcProvider {
+    /// Адрес страницы входа провайдера для authorization code flow с PKCE
+    pub fn authorization_url(&self, pkce: &Pkce, state: &str, nonce: &str) -> Result<String> {
+        let url = Url::parse_with_params(
+            &self.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("scope", self.scopes.join(" ").as_str()),
+                ("state", state),
+                ("nonce", nonce),
+                ("code_challenge", pkce.challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )?;
+
+        Ok(url.to_string())
+    }
+}
+
+/// Пара PKCE (RFC 7636)
+pub struct Pkce {
+    verifier: String,
+    challenge: String,
+}
+
+impl Pkce {
+    pub fn new() -> Self {
+        let verifier = random_string();
+        let challenge = base64::encode_config(
+            ring::digest::digest(&ring::digest::SHA256, verifier.as_bytes()),
+            base64::URL_SAFE_NO_PAD,
+        );
+
+        Self {
+            verifier,
+            challenge,
+        }
+    }
+
+    pub fn verifier(&self) -> &str {
+        &self.verifier
+    }
+}
+
+/// Случайная строка для `state`, `nonce` и PKCE
+pub fn random_string() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+/// Утверждения ID токена, необходимые для входа
+#[derive(Debug, Deserialize)]
+pub struct IdClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    email_verified: bool,
+}
+
+impl IdClaims {
+    pub fn sub(&self) -> &str {
+        &self.sub
+    }
+
+    /// Адрес почты, если провайдер подтвердил владение им
+    pub fn verified_email(&self) -> Option<&str> {
+        match self.email_verified {
+            true => self.email.as_deref(),
+            false => None,
+        }
+    }
+}
+
+/// Некоторые провайдеры передают `email_verified` строкой
+fn deserialize_flag<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match <serde_json::Value as serde::Deserialize>::deserialize(deserializer)? {
+        serde_json::Value::Bool(value) => Ok(value),
+        serde_json::Value::String(value) => Ok(value == "true"),
+        _ => Ok(false),
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// Открытый ключ провайдера в формате JWK
+#[derive(Deserialize)]
+struct RemoteJwk {
+    kty: String,
+    kid: Option<String>,
+    crv: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+    x: Option<String>,
+    y: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RemoteJwkSet {
+    keys: Vec<RemoteJwk>,
+}
+
+impl RemoteJwk {
+    fn decoding_key(&self) -> Result<DecodingKey> {
+        let field = |value: &Option<String>| {
+            value
+                .as_deref()
+                .ok_or_else(|| anyhow!("JWK of type `{}` is incomplete", self.kty))
+        };
+        let decode = |value: &str| base64::decode_config(value, base64::URL_SAFE_NO_PAD);
+
+        match (self.kty.as_str(), self.crv.as_deref()) {
+            ("RSA", _) => Ok(DecodingKey::from_rsa_components(
+                field(&self.n)?,
+                field(&self.e)?,
+            )?),
+
+            // Несжатая точка кривой: 0x04 || x || y
+            ("EC", Some("P-256")) | ("EC", Some("P-384")) => {
+                let mut point = vec![0x04];
+                point.extend(decode(field(&self.x)?)?);
+                point.extend(decode(field(&self.y)?)?);
+
+                Ok(DecodingKey::from_ec_der(&point))
+            }
+
+            ("OKP", Some("Ed25519")) => Ok(DecodingKey::from_ed_der(&decode(field(&self.x)?)?)),
+
+            (kty, _) => Err(anyhow!("JWK of type `{}` is not supported", kty)),
+        }
+    }
+}
+
+struct CachedJwks {
+    keys: Vec<RemoteJwk>,
+    fetched_at: i64,
+}
+
+/// Клиент провайдеров OpenID Connect.
+///
+/// Наборы ключей провайдеров кэшируются и загружаются повторно
+/// по истечении срока или при появлении неизвестного `kid`.
+pub struct OidcClient {
+    http: reqwest::Client,
+    jwks: Mutex<HashMap<String, CachedJwks>>,
+    /// `nonce` завершенных входов до истечения срока их токенов
+    used_flows: Mutex<HashMap<String, i64>>,
+}
+
+impl OidcClient {
+    pub fn new() -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            jwks: Mutex::new(HashMap::new()),
+            used_flows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Отметить вход с `nonce` как начатый.
+    ///
+    /// Возвращает `false`, если токен входа уже предъявлялся.
+    /// Запись хранится до `exp`, после него токен не пройдет проверку подписи.
+    pub fn use_flow(&self, nonce: &str, exp: i64) -> bool {
+        let now = Utc::now().timestamp();
+        let mut used_flows = self.used_flows.lock().unwrap();
+
+        used_flows.retain(|_, exp| *exp > now);
+        used_flows.insert(nonce.to_string(), exp).is_none()
+    }
+
+    /// Обмен кода авторизации на ID токен и его проверка.
+    ///
+    /// Проверяются подпись, издатель, получатель, срок действия
+    /// и `nonce`, переданный при перенаправлении на провайдера.
+    pub async fn exchange_code<'a>(
+        &self,
+        provider: &OidcProvider,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<IdClaims, CustomError<'a>> {
+        let mut params = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", provider.redirect_uri.as_str()),
+            ("client_id", provider.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(client_secret) = &provider.client_secret {
+            params.push(("client_secret", client_secret.as_str()));
+        }
+
+        let response = self
+            .http
+            .post(&provider.token_endpoint)
+            .form(&params)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| crate::internal!(&err.to_string()))?
+            .json::<TokenResponse>()
+            .await
+            .map_err(|err| crate::internal!(&err.to_string()))?;
+
+        let claims = self.validate_id_token(provider, &response.id_token).await?;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(CustomError::new().kind(TokenInvalid).build());
+        }
+
+        Ok(claims)
+    }
+
+    async fn validate_id_token<'a>(
+        &self,
+        provider: &OidcProvider,
+        id_token: &str,
+    ) -> Result<IdClaims, CustomError<'a>> {
+        let header = jsonwebtoken::decode_header(id_token)?;
+
+        // Симметричные алгоритмы не принимаются, иначе токен
+        // можно подписать открытым ключом провайдера
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(CustomError::new()
+                .kind(TokenInvalid)
+                .details("Unsupported ID token algorithm")
+                .build());
+        }
+
+        let key = self.decoding_key(provider, header.kid.as_deref()).await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&provider.issuer]);
+        validation.set_audience(&[&provider.client_id]);
+
+        Ok(jsonwebtoken::decode::<IdClaims>(id_token, &key, &validation)?.claims)
+    }
+
+    async fn decoding_key<'a>(
+        &self,
+        provider: &OidcProvider,
+        kid: Option<&str>,
+    ) -> Result<DecodingKey, CustomError<'a>> {
+        if let Some(key) = self.cached_key(provider, kid, false)? {
+            return Ok(key);
+        }
+
+        let jwks = self
+            .http
+            .get(&provider.jwks_uri)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| crate::internal!(&err.to_string()))?
+            .json::<RemoteJwkSet>()
+            .await
+            .map_err(|err| crate::internal!(&err.to_string()))?;
+
+        self.jwks.lock().unwrap().insert(
+            provider.name.clone(),
+            CachedJwks {
+                keys: jwks.keys,
+                fetched_at: Utc::now().timestamp(),
+            },
+        );
+
+        self.cached_key(provider, kid, true)?
+            .ok_or_else(|| CustomError::new().kind(TokenInvalid).build())
+    }
+
+    /// Поиск ключа в кэше.
+    /// Без `kid` подходит только единственный ключ набора.
+    fn cached_key<'a>(
+        &self,
+        provider: &OidcProvider,
+        kid: Option<&str>,
+        fresh: bool,
+    ) -> Result<Option<DecodingKey>, CustomError<'a>> {
+        let cache = self.jwks.lock().unwrap();
+
+        let jwks = match cache.get(&provider.name) {
+            Some(jwks) if fresh || Utc::now().timestamp() - jwks.fetched_at < JWKS_TTL => jwks,
+            _ => return Ok(None),
+        };
+
+        let jwk = match kid {
+            Some(kid) => jwks.keys.iter().find(|jwk| jwk.kid.as_deref() == Some(kid)),
+            None if jwks.keys.len() == 1 => jwks.keys.first(),
+            None => None,
+        };
+
+        match jwk {
+            Some(jwk) => Ok(Some(
+                jwk.decoding_key()
+                    .map_err(|err| crate::internal!(&err.to_string()))?,
+            )),
+            None => Ok(None),
+        }
+    }
+}
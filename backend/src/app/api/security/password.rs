@@ -0,0 +1,368 @@
+use anyhow::{anyhow, Result};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use validator::ValidationError;
+
+lazy_static! {
+    /// Список утекших паролей из файла `BREACHED_PASSWORDS_PATH`.
+    /// Пустой путь отключает проверку.
+    pub static ref BREACHED_PASSWORDS: BreachedPasswords =
+        BreachedPasswords::load(dotenv!("BREACHED_PASSWORDS_PATH"))
+            .expect("Failed to load breached passwords");
+}
+
+/// Минимальная длина пароля в символах
+pub const PASSWORD_MIN_LENGTH: usize = 8;
+/// Максимальная длина пароля в символах, достаточная для парольной фразы
+pub const PASSWORD_MAX_LENGTH: usize = 128;
+/// Минимальная оценка стойкости пароля в битах
+const MIN_ENTROPY: f64 = 50.0;
+/// Вес символа, продолжающего повтор или последовательность
+const PATTERN_WEIGHT: f64 = 0.2;
+
+/// Ряды клавиатуры для поиска последовательностей вида `qwerty`
+const KEYBOARD_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// Нарушение парольной политики
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordViolation {
+    TooShort,
+    TooLong,
+    ControlCharacters,
+    Weak,
+    Breached,
+}
+
+impl PasswordViolation {
+    pub fn code(&self) -> &'static str {
+        match self {
+            PasswordViolation::TooShort => "TOO_SHORT",
+            PasswordViolation::TooLong => "TOO_LONG",
+            PasswordViolation::ControlCharacters => "CONTROL_CHARACTERS",
+            PasswordViolation::Weak => "WEAK",
+            PasswordViolation::Breached => "BREACHED",
+        }
+    }
+}
+
+/// Шаблоны, снижающие стойкость пароля
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordPattern {
+    /// Повтор символа: `aaa`
+    Repeat,
+    /// Алфавитная или цифровая последовательность: `abc`, `321`
+    Sequence,
+    /// Соседние клавиши: `qwe`, `lkj`
+    Keyboard,
+}
+
+impl PasswordPattern {
+    pub fn code(&self) -> &'static str {
+        match self {
+            PasswordPattern::Repeat => "REPEAT",
+            PasswordPattern::Sequence => "SEQUENCE",
+            PasswordPattern::Keyboard => "KEYBOARD",
+        }
+    }
+}
+
+/// Оценка стойкости пароля
+#[derive(Debug, Clone)]
+pub struct PasswordStrength {
+    /// Оценка энтропии в битах с учетом найденных шаблонов
+    pub entropy: f64,
+    pub patterns: Vec<PasswordPattern>,
+}
+
+impl PasswordStrength {
+    /// Энтропия оценивается как `длина * log2(алфавит)`, где алфавит
+    /// складывается из групп использованных символов. Символы,
+    /// продолжающие повтор или последовательность, почти не добавляют
+    /// стойкости и учитываются с весом `PATTERN_WEIGHT`.
+    pub fn estimate(password: &str) -> Self {
+        let chars = password.chars().collect::<Vec<_>>();
+
+        let mut pool = 0;
+        if chars.iter().any(|c| c.is_ascii_lowercase()) {
+            pool += 26;
+        }
+        if chars.iter().any(|c| c.is_ascii_uppercase()) {
+            pool += 26;
+        }
+        if chars.iter().any(|c| c.is_ascii_digit()) {
+            pool += 10;
+        }
+        if chars.iter().any(|c| c.is_ascii_punctuation() || *c == ' ') {
+            pool += 33;
+        }
+        // Алфавиты, отличные от латиницы, оцениваются грубо
+        if chars.iter().any(|c| !c.is_ascii()) {
+            pool += 100;
+        }
+
+        let mut patterns = Vec::new();
+        let mut length = 0.0;
+
+        for (i, c) in chars.iter().enumerate() {
+            let pattern = match i {
+                0 => None,
+                _ => find_pattern(chars[i - 1], *c),
+            };
+
+            match pattern {
+                Some(pattern) => {
+                    length += PATTERN_WEIGHT;
+
+                    if !patterns.contains(&pattern) {
+                        patterns.push(pattern);
+                    }
+                }
+                None => length += 1.0,
+            }
+        }
+
+        let entropy = match pool {
+            0 => 0.0,
+            _ => length * (pool as f64).log2(),
+        };
+
+        Self { entropy, patterns }
+    }
+}
+
+/// Шаблон, который продолжает символ `next` после символа `prev`
+fn find_pattern(prev: char, next: char) -> Option<PasswordPattern> {
+    let prev = prev.to_lowercase().next().unwrap_or(prev);
+    let next = next.to_lowercase().next().unwrap_or(next);
+
+    if prev == next {
+        return Some(PasswordPattern::Repeat);
+    }
+
+    if prev.is_alphanumeric() && (prev as u32).abs_diff(next as u32) == 1 {
+        return Some(PasswordPattern::Sequence);
+    }
+
+    let adjacent = KEYBOARD_ROWS.iter().any(|row| {
+        let row = row.chars().collect::<Vec<_>>();
+
+        row.windows(2)
+            .any(|pair| pair == [prev, next] || pair == [next, prev])
+    });
+
+    if adjacent {
+        return Some(PasswordPattern::Keyboard);
+    }
+
+    None
+}
+
+/// Список утекших паролей.
+///
+/// Файл содержит по одной строке на пароль: начало SHA-1 хеша пароля
+/// в шестнадцатеричном виде, после двоеточия может идти количество
+/// утечек, как в выгрузках Have I Been Pwned. Длина префикса должна
+/// быть одинаковой во всем файле, строки с `#` считаются комментариями.
+///
+/// Короткий префикс уменьшает размер файла ценой ложных срабатываний.
+pub struct BreachedPasswords {
+    prefix_len: usize,
+    prefixes: HashSet<String>,
+}
+
+impl BreachedPasswords {
+    pub fn load(path: &str) -> Result<Self> {
+        if path.is_empty() {
+            return Ok(Self {
+                prefix_len: 0,
+                prefixes: HashSet::new(),
+            });
+        }
+
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    fn parse(data: &str) -> Result<Self> {
+        let mut prefix_len = 0;
+        let mut prefixes = HashSet::new();
+
+        let lines = data
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        for line in lines {
+            let prefix = line.split(':').next().unwrap_or_default().to_uppercase();
+
+            if prefix.len() < 5
+                || prefix.len() > 40
+                || !prefix.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                return Err(anyhow!("Invalid hash prefix `{}`", prefix));
+            }
+
+            match prefix_len {
+                0 => prefix_len = prefix.len(),
+                len if len != prefix.len() => {
+                    return Err(anyhow!("Hash prefixes must have the same length"))
+                }
+                _ => (),
+            }
+
+            prefixes.insert(prefix);
+        }
+
+        Ok(Self {
+            prefix_len,
+            prefixes,
+        })
+    }
+
+    pub fn contains(&self, password: &str) -> bool {
+        if self.prefixes.is_empty() {
+            return false;
+        }
+
+        let digest =
+            ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, password.as_bytes());
+        let hash = digest
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<String>();
+
+        self.prefixes.contains(&hash[..self.prefix_len])
+    }
+}
+
+/// Проверка пароля по парольной политике.
+/// Возвращает все найденные нарушения.
+pub fn check_password(password: &str) -> (Vec<PasswordViolation>, PasswordStrength) {
+    let mut violations = Vec::new();
+    let length = password.chars().count();
+
+    if length < PASSWORD_MIN_LENGTH {
+        violations.push(PasswordViolation::TooShort);
+    }
+
+    if length > PASSWORD_MAX_LENGTH {
+        violations.push(PasswordViolation::TooLong);
+    }
+
+    if password.chars().any(|c| c.is_control()) {
+        violations.push(PasswordViolation::ControlCharacters);
+    }
+
+    let strength = PasswordStrength::estimate(password);
+
+    if strength.entropy < MIN_ENTROPY {
+        violations.push(PasswordViolation::Weak);
+    }
+
+    if BREACHED_PASSWORDS.contains(password) {
+        violations.push(PasswordViolation::Breached);
+    }
+
+    (violations, strength)
+}
+
+/// Валидатор нового пароля для `validator`.
+///
+/// Ошибка содержит коды всех нарушений в параметре `violations`,
+/// оценку стойкости и найденные шаблоны, но не сам пароль.
+pub fn validate_password(password: &str) -> Result<(), ValidationError> {
+    let (violations, strength) = check_password(password);
+
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    let mut err = ValidationError::new("password_policy");
+    err.message = Some(Cow::from("Password does not satisfy the password policy"));
+
+    err.add_param(
+        Cow::from("violations"),
+        &violations
+            .iter()
+            .map(|violation| violation.code())
+            .collect::<Vec<_>>(),
+    );
+    err.add_param(Cow::from("entropy"), &strength.entropy.floor());
+    err.add_param(Cow::from("min_entropy"), &MIN_ENTROPY);
+    err.add_param(
+        Cow::from("patterns"),
+        &strength
+            .patterns
+            .iter()
+            .map(|pattern| pattern.code())
+            .collect::<Vec<_>>(),
+    );
+    err.add_param(Cow::from("min_length"), &PASSWORD_MIN_LENGTH);
+    err.add_param(Cow::from("max_length"), &PASSWORD_MAX_LENGTH);
+
+    Err(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_password_has_no_entropy() {
+        assert_eq!(PasswordStrength::estimate("").entropy, 0.0);
+    }
+
+    #[test]
+    fn random_password_is_strong() {
+        let strength = PasswordStrength::estimate("Vq7#pL2m!xR9");
+
+        assert!(strength.patterns.is_empty());
+        assert!(strength.entropy >= MIN_ENTROPY);
+    }
+
+    #[test]
+    fn patterns_lower_entropy() {
+        let plain = PasswordStrength::estimate("kbmzfxht");
+        let repeat = PasswordStrength::estimate("kkkkkkkk");
+
+        assert!(plain.patterns.is_empty());
+        assert_eq!(repeat.patterns, vec![PasswordPattern::Repeat]);
+        assert!(repeat.entropy < plain.entropy);
+        assert!(repeat.entropy < MIN_ENTROPY);
+    }
+
+    #[test]
+    fn finds_patterns() {
+        assert_eq!(find_pattern('a', 'a'), Some(PasswordPattern::Repeat));
+        assert_eq!(find_pattern('A', 'a'), Some(PasswordPattern::Repeat));
+        assert_eq!(find_pattern('b', 'c'), Some(PasswordPattern::Sequence));
+        assert_eq!(find_pattern('3', '2'), Some(PasswordPattern::Sequence));
+        assert_eq!(find_pattern('q', 'w'), Some(PasswordPattern::Keyboard));
+        assert_eq!(find_pattern('k', 'j'), Some(PasswordPattern::Keyboard));
+        assert_eq!(find_pattern('!', '"'), None);
+        assert_eq!(find_pattern('a', 'z'), None);
+    }
+
+    #[test]
+    fn breached_prefix_matches() {
+        let breached = BreachedPasswords::parse("# comment\n5baa6:3\n\n2F9E5\n").unwrap();
+
+        assert!(breached.contains("password"));
+        assert!(breached.contains("correct horse"));
+        assert!(!breached.contains("Vq7#pL2m!xR9"));
+    }
+
+    #[test]
+    fn empty_breached_list_matches_nothing() {
+        let breached = BreachedPasswords::load("").unwrap();
+
+        assert!(!breached.contains("password"));
+    }
+
+    #[test]
+    fn rejects_invalid_prefixes() {
+        assert!(BreachedPasswords::parse("5BAA\n").is_err());
+        assert!(BreachedPasswords::parse("5BAAZ\n").is_err());
+        assert!(BreachedPasswords::parse("5BAA6\n2F9E53\n").is_err());
+    }
+}
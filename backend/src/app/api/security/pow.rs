@@ -0,0 +1,318 @@
+use async_graphql::{InputObject, SimpleObject};
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use super::auth::{ActionClaims, Token, TokenPurpose};
+use crate::app::core::error::CustomError;
+use crate::model::profile::profile_error::{
+    ERR_PROF__CHALLENGE_OUTDATED, ERR_PROF__CHALLENGE_UNSOLVED, ERR_PROF__CHALLENGE_USED,
+};
+
+/// Сложность задачи без всплеска регистраций, в нулевых битах хеша.
+/// Решается в браузере примерно за секунду.
+const BASE_DIFFICULTY: u32 = 18;
+/// Предельная сложность задачи
+const MAX_DIFFICULTY: u32 = 26;
+/// Срок действия задачи в минутах
+const CHALLENGE_MINUTES: i64 = 10;
+/// Окно учета регистраций в секундах
+const RATE_WINDOW: i64 = 60 * 60;
+/// Количество регистраций с одного IP адреса за окно,
+/// после которого сложность начинает расти
+const IP_THRESHOLD: usize = 3;
+/// То же для подсети: /24 для IPv4 и /48 для IPv6
+const SUBNET_THRESHOLD: usize = 10;
+
+/// Задача для регистрации.
+///
+/// Клиенту нужно подобрать `nonce`, при котором SHA-256 от строки
+/// `{seed}:{nonce}` начинается не менее чем с `difficulty` нулевых бит.
+/// Задача подписана сервером и передается обратно вместе с решением.
+#[derive(SimpleObject)]
+pub struct RegistrationChallenge {
+    token: String,
+    seed: String,
+    difficulty: u32,
+    expires_at: i64,
+}
+
+/// Решение задачи регистрации
+#[derive(Serialize, Deserialize, InputObject)]
+pub struct ChallengeSolution {
+    /// Токен из `registrationChallenge`
+    pub(crate) token: String,
+    pub(crate) nonce: String,
+}
+
+/// Проверенное решение задачи, еще не учтенное в `ProofOfWork`
+pub struct SolvedChallenge {
+    seed: String,
+    expires_at: i64,
+}
+
+/// Доказательство работы для анонимной регистрации.
+///
+/// Сложность задачи растет на бит с каждым удвоением количества
+/// регистраций с IP адреса или подсети сверх порога. Решение
+/// принимается один раз и только если сложность задачи не ниже
+/// текущей, поэтому заранее накопленные простые задачи бесполезны.
+pub struct ProofOfWork {
+    /// Использованные задачи до истечения их срока
+    used: Mutex<HashMap<String, i64>>,
+    /// Время регистраций по IP адресам и подсетям
+    registrations: Mutex<HashMap<String, Vec<i64>>>,
+}
+
+impl Default for ProofOfWork {
+    fn default() -> Self {
+        Self {
+            used: Mutex::new(HashMap::new()),
+            registrations: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl ProofOfWork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Текущая сложность задачи для клиента
+    pub fn difficulty(&self, ip: Option<&str>) -> u32 {
+        let now = Utc::now().timestamp();
+        let registrations = self.registrations.lock().unwrap();
+
+        let count = |key: &str| {
+            registrations
+                .get(key)
+                .map(|times| {
+                    times
+                        .iter()
+                        .filter(|time| **time > now - RATE_WINDOW)
+                        .count()
+                })
+                .unwrap_or(0)
+        };
+
+        let extra = rate_keys(ip)
+            .map(|(ip, subnet)| {
+                extra_difficulty(count(&ip), IP_THRESHOLD)
+                    .max(extra_difficulty(count(&subnet), SUBNET_THRESHOLD))
+            })
+            .unwrap_or(0);
+
+        (BASE_DIFFICULTY + extra).min(MAX_DIFFICULTY)
+    }
+
+    /// Выдать новую задачу
+    pub fn issue(&self, ip: Option<&str>) -> Result<RegistrationChallenge, CustomError> {
+        let seed = Uuid::new_v4().to_string();
+        let difficulty = self.difficulty(ip);
+
+        let claims = ActionClaims::new(
+            seed.clone(),
+            TokenPurpose::RegistrationChallenge,
+            Some(difficulty.to_string()),
+            Duration::minutes(CHALLENGE_MINUTES),
+        );
+        let expires_at = claims.exp();
+
+        let token = Token::encode(claims).map_err(|err| crate::internal!(&err.to_string()))?;
+
+        Ok(RegistrationChallenge {
+            token,
+            seed,
+            difficulty,
+            expires_at,
+        })
+    }
+
+    /// Проверить решение задачи.
+    ///
+    /// Задача не помечается использованной, это делает `commit`
+    /// после успешной регистрации.
+    pub fn check<'a>(
+        &self,
+        ip: Option<&str>,
+        solution: &ChallengeSolution,
+    ) -> Result<SolvedChallenge, CustomError<'a>> {
+        let claims = ActionClaims::decode(&solution.token, TokenPurpose::RegistrationChallenge)?;
+        let difficulty = claims
+            .data()
+            .and_then(|data| data.parse::<u32>().ok())
+            .unwrap_or(0);
+
+        if difficulty < self.difficulty(ip) {
+            return Err(crate::unprocessable!(
+                "challenge",
+                Some(ERR_PROF__CHALLENGE_OUTDATED.to_string())
+            ));
+        }
+
+        if leading_zero_bits(claims.sub(), &solution.nonce) < difficulty {
+            return Err(crate::unprocessable!(
+                "challenge",
+                Some(ERR_PROF__CHALLENGE_UNSOLVED.to_string())
+            ));
+        }
+
+        if self.used.lock().unwrap().contains_key(claims.sub()) {
+            return Err(crate::unprocessable!(
+                "challenge",
+                Some(ERR_PROF__CHALLENGE_USED.to_string())
+            ));
+        }
+
+        Ok(SolvedChallenge {
+            seed: claims.sub().to_string(),
+            expires_at: claims.exp(),
+        })
+    }
+
+    /// Пометить задачу использованной и учесть регистрацию клиента
+    pub fn commit(&self, ip: Option<&str>, challenge: SolvedChallenge) {
+        {
+            let now = Utc::now().timestamp();
+            let mut used = self.used.lock().unwrap();
+
+            // Истекшие задачи уже не пройдут проверку подписи
+            used.retain(|_, exp| *exp > now);
+            used.insert(challenge.seed, challenge.expires_at);
+        }
+
+        self.record(ip);
+    }
+
+    fn record(&self, ip: Option<&str>) {
+        let (ip, subnet) = match rate_keys(ip) {
+            Some(keys) => keys,
+            None => return,
+        };
+
+        let now = Utc::now().timestamp();
+        let mut registrations = self.registrations.lock().unwrap();
+
+        registrations.retain(|_, times| {
+            times.retain(|time| *time > now - RATE_WINDOW);
+            !times.is_empty()
+        });
+
+        for key in [ip, subnet] {
+            registrations.entry(key).or_default().push(now);
+        }
+    }
+}
+
+/// Ключи учета регистраций: адрес клиента и его подсеть
+fn rate_keys(ip: Option<&str>) -> Option<(String, String)> {
+    let ip = ip?;
+    let addr = ip
+        .parse::<IpAddr>()
+        .or_else(|_| ip.parse::<SocketAddr>().map(|addr| addr.ip()))
+        .ok()?;
+
+    let subnet = match addr {
+        IpAddr::V4(addr) => {
+            let [a, b, c, _] = addr.octets();
+            format!("{}.{}.{}.0/24", a, b, c)
+        }
+        IpAddr::V6(addr) => {
+            let segments = addr.segments();
+            format!("{:x}:{:x}:{:x}::/48", segments[0], segments[1], segments[2])
+        }
+    };
+
+    Some((format!("ip:{}", addr), format!("subnet:{}", subnet)))
+}
+
+/// Дополнительная сложность: бит за каждое удвоение порога
+fn extra_difficulty(count: usize, threshold: usize) -> u32 {
+    match count < threshold {
+        true => 0,
+        false => 1 + (count / threshold).ilog2(),
+    }
+}
+
+/// Количество ведущих нулевых бит SHA-256 от `{seed}:{nonce}`
+fn leading_zero_bits(seed: &str, nonce: &str) -> u32 {
+    let digest = ring::digest::digest(
+        &ring::digest::SHA256,
+        format!("{}:{}", seed, nonce).as_bytes(),
+    );
+
+    let mut bits = 0;
+    for byte in digest.as_ref() {
+        bits += byte.leading_zeros();
+
+        if *byte != 0 {
+            break;
+        }
+    }
+
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_leading_zero_bits() {
+        // SHA-256("seed:293") = 00427d77...
+        assert_eq!(leading_zero_bits("seed", "293"), 9);
+        // SHA-256("seed:1") начинается с бита 1
+        assert_eq!(leading_zero_bits("seed", "1"), 0);
+    }
+
+    #[test]
+    fn extra_difficulty_grows_per_doubling() {
+        assert_eq!(extra_difficulty(0, 3), 0);
+        assert_eq!(extra_difficulty(2, 3), 0);
+        assert_eq!(extra_difficulty(3, 3), 1);
+        assert_eq!(extra_difficulty(5, 3), 1);
+        assert_eq!(extra_difficulty(6, 3), 2);
+        assert_eq!(extra_difficulty(12, 3), 3);
+    }
+
+    #[test]
+    fn difficulty_grows_with_registrations() {
+        let pow = ProofOfWork::new();
+        let ip = Some("203.0.113.7");
+
+        assert_eq!(pow.difficulty(ip), BASE_DIFFICULTY);
+
+        for _ in 0..IP_THRESHOLD {
+            pow.record(ip);
+        }
+
+        assert_eq!(pow.difficulty(ip), BASE_DIFFICULTY + 1);
+        assert_eq!(pow.difficulty(Some("198.51.100.7")), BASE_DIFFICULTY);
+        assert_eq!(pow.difficulty(None), BASE_DIFFICULTY);
+    }
+
+    #[test]
+    fn difficulty_counts_subnet() {
+        let pow = ProofOfWork::new();
+
+        for i in 0..SUBNET_THRESHOLD {
+            pow.record(Some(&format!("203.0.113.{}:443", i)));
+        }
+
+        assert_eq!(pow.difficulty(Some("203.0.113.200")), BASE_DIFFICULTY + 1);
+    }
+
+    #[test]
+    fn difficulty_is_capped() {
+        let pow = ProofOfWork::new();
+        let ip = Some("203.0.113.7");
+
+        for _ in 0..IP_THRESHOLD << (MAX_DIFFICULTY - BASE_DIFFICULTY + 1) {
+            pow.record(ip);
+        }
+
+        assert_eq!(pow.difficulty(ip), MAX_DIFFICULTY);
+    }
+}
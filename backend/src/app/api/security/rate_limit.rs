@@ -0,0 +1,409 @@
+use anyhow::{anyhow, Result};
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextRequest, NextResolve, ResolveInfo,
+};
+use async_graphql::{
+    Error as GraphQLError, ErrorExtensions, PathSegment, Response, ServerError, ServerResult, Value,
+};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{auth::AccessClaims, client::ClientInfo};
+use crate::app::core::error::{CustomError, CustomErrorKind::TooManyRequests};
+use crate::model::profile::profile_model::Permission;
+
+lazy_static! {
+    /// Ограничения частоты запросов из раздела `rate_limits` файла `LIMITS_PATH`.
+    /// Пустой путь или отсутствующие в файле значения заменяются значениями по умолчанию.
+    pub static ref RATE_LIMITS: RateLimitsConfig =
+        load_limits(dotenv!("LIMITS_PATH")).expect("Failed to load rate limits");
+}
+
+/// Параметры корзины токенов: `capacity` запросов подряд,
+/// которые равномерно восстанавливаются за `period` секунд.
+#[derive(Clone, Copy, Deserialize)]
+struct BucketPolicy {
+    capacity: u32,
+    period: u32,
+}
+
+impl BucketPolicy {
+    const fn per_minute(capacity: u32) -> Self {
+        Self {
+            capacity,
+            period: 60,
+        }
+    }
+
+    const fn per_hour(capacity: u32) -> Self {
+        Self {
+            capacity,
+            period: 60 * 60,
+        }
+    }
+
+    /// Количество восстанавливаемых запросов в секунду
+    fn rate(&self) -> f64 {
+        self.capacity as f64 / self.period as f64
+    }
+}
+
+/// Ограничения для роли
+struct RateLimits {
+    /// Все запросы клиента
+    client: BucketPolicy,
+    /// Отдельные операции, ключ - имя корневого поля
+    operations: HashMap<String, BucketPolicy>,
+}
+
+impl RateLimits {
+    fn new(client: BucketPolicy, operations: &[(&str, BucketPolicy)]) -> Self {
+        Self {
+            client,
+            operations: operations
+                .iter()
+                .map(|(operation, policy)| (operation.to_string(), *policy))
+                .collect(),
+        }
+    }
+
+    fn operation(&self, name: &str) -> Option<BucketPolicy> {
+        self.operations.get(name).copied()
+    }
+
+    fn policies(&self) -> impl Iterator<Item = &BucketPolicy> {
+        std::iter::once(&self.client).chain(self.operations.values())
+    }
+
+    /// Замена значений по умолчанию заданными в файле.
+    /// Операции, не указанные в файле, сохраняют ограничения по умолчанию.
+    fn merge(&mut self, file: RateLimitsFile) {
+        if let Some(client) = file.client {
+            self.client = client;
+        }
+
+        self.operations.extend(file.operations);
+    }
+}
+
+/// Ограничения роли в файле `LIMITS_PATH`
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RateLimitsFile {
+    client: Option<BucketPolicy>,
+    operations: HashMap<String, BucketPolicy>,
+}
+
+/// Ограничения частоты запросов по ролям
+pub struct RateLimitsConfig {
+    /// Все запросы с одного IP адреса независимо от профиля,
+    /// чтобы ограничения нельзя было обойти множеством токенов
+    ip: BucketPolicy,
+    /// Ограничения анонимных клиентов, они учитываются по IP адресу
+    guest: RateLimits,
+    user: RateLimits,
+    developer: RateLimits,
+    admin: RateLimits,
+}
+
+impl Default for RateLimitsConfig {
+    fn default() -> Self {
+        Self {
+            ip: BucketPolicy::per_minute(600),
+            guest: RateLimits::new(
+                BucketPolicy::per_minute(60),
+                &[
+                    ("registrationChallenge", BucketPolicy::per_minute(10)),
+                    ("registration", BucketPolicy::per_hour(5)),
+                    ("login", BucketPolicy::per_minute(10)),
+                    ("loginSecondFactor", BucketPolicy::per_minute(10)),
+                    ("oidcLogin", BucketPolicy::per_minute(10)),
+                    ("requestPasswordReset", BucketPolicy::per_hour(5)),
+                    ("resetPassword", BucketPolicy::per_hour(10)),
+                    ("refreshTokens", BucketPolicy::per_minute(10)),
+                ],
+            ),
+            user: RateLimits::new(
+                BucketPolicy::per_minute(300),
+                &[
+                    ("login", BucketPolicy::per_minute(10)),
+                    ("loginSecondFactor", BucketPolicy::per_minute(10)),
+                    ("subscribe", BucketPolicy::per_minute(30)),
+                    ("resendVerificationEmail", BucketPolicy::per_hour(5)),
+                    ("changePassword", BucketPolicy::per_hour(10)),
+                    ("deleteAccount", BucketPolicy::per_hour(5)),
+                    ("refreshTokens", BucketPolicy::per_minute(10)),
+                ],
+            ),
+            developer: RateLimits::new(
+                BucketPolicy::per_minute(1200),
+                &[
+                    ("login", BucketPolicy::per_minute(10)),
+                    ("loginSecondFactor", BucketPolicy::per_minute(10)),
+                    ("subscribe", BucketPolicy::per_minute(120)),
+                    ("createApiKey", BucketPolicy::per_hour(20)),
+                ],
+            ),
+            admin: RateLimits::new(BucketPolicy::per_minute(3000), &[]),
+        }
+    }
+}
+
+impl RateLimitsConfig {
+    fn limits(&self, permission: Permission) -> &RateLimits {
+        match permission {
+            Permission::Guest => &self.guest,
+            Permission::User => &self.user,
+            Permission::Developer => &self.developer,
+            Permission::Admin => &self.admin,
+        }
+    }
+}
+
+/// Раздел `rate_limits` файла `LIMITS_PATH`
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RateLimitsConfigFile {
+    ip: Option<BucketPolicy>,
+    guest: RateLimitsFile,
+    user: RateLimitsFile,
+    developer: RateLimitsFile,
+    admin: RateLimitsFile,
+}
+
+#[derive(Deserialize)]
+struct LimitsFile {
+    #[serde(default)]
+    rate_limits: RateLimitsConfigFile,
+}
+
+fn load_limits(path: &str) -> Result<RateLimitsConfig> {
+    let mut config = RateLimitsConfig::default();
+
+    if path.is_empty() {
+        return Ok(config);
+    }
+
+    let file: LimitsFile = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let file = file.rate_limits;
+
+    if let Some(ip) = file.ip {
+        config.ip = ip;
+    }
+    config.guest.merge(file.guest);
+    config.user.merge(file.user);
+    config.developer.merge(file.developer);
+    config.admin.merge(file.admin);
+
+    // Нулевые значения дают бесконечное время восстановления корзины
+    let roles = [
+        &config.guest,
+        &config.user,
+        &config.developer,
+        &config.admin,
+    ];
+    let invalid = roles
+        .iter()
+        .flat_map(|limits| limits.policies())
+        .chain(std::iter::once(&config.ip))
+        .any(|policy| policy.capacity == 0 || policy.period == 0);
+
+    if invalid {
+        return Err(anyhow!("Rate limit capacity and period must be positive"));
+    }
+
+    Ok(config)
+}
+
+/// Интервал удаления заполненных корзин
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    /// Момент, когда корзина заполнится и её можно будет удалить
+    full_at: Instant,
+}
+
+struct Buckets {
+    buckets: HashMap<String, Bucket>,
+    last_cleanup: Instant,
+}
+
+/// Ограничение частоты запросов по алгоритму корзины токенов.
+///
+/// Запросы учитываются по IP адресу, по профилю (`sub` Access токена)
+/// и по операции - имени корневого поля запроса. Анонимный клиент
+/// учитывается по IP адресу с ограничениями роли `Guest`.
+pub struct RateLimiter {
+    state: Mutex<Buckets>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                last_cleanup: Instant::now(),
+            }),
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Учесть запрос к операции.
+    /// При превышении ограничения возвращает время ожидания в секундах.
+    pub fn check(
+        &self,
+        ip: Option<&str>,
+        claims: Option<&AccessClaims>,
+        operation: &str,
+    ) -> Result<(), u64> {
+        let (client, permission) = match (claims, ip) {
+            (Some(claims), _) => (format!("sub:{}", claims.sub()), claims.permission()),
+            (None, Some(ip)) => (format!("guest:{}", ip), Permission::Guest),
+            (None, None) => return Ok(()),
+        };
+        let limits = RATE_LIMITS.limits(permission);
+
+        if let Some(ip) = ip {
+            self.take(format!("ip:{}", ip), RATE_LIMITS.ip)?;
+        }
+
+        self.take(client.clone(), limits.client)?;
+
+        match limits.operation(operation) {
+            Some(policy) => self.take(format!("{}:{}", client, operation), policy),
+            None => Ok(()),
+        }
+    }
+
+    fn take(&self, key: String, policy: BucketPolicy) -> Result<(), u64> {
+        let now = Instant::now();
+        let capacity = policy.capacity as f64;
+        let rate = policy.rate();
+
+        let mut state = self.state.lock().unwrap();
+
+        // Заполненная корзина ничем не отличается от отсутствующей
+        if now.duration_since(state.last_cleanup) > CLEANUP_INTERVAL {
+            state.buckets.retain(|_, bucket| bucket.full_at > now);
+            state.last_cleanup = now;
+        }
+
+        let bucket = state.buckets.entry(key).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+            full_at: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated = now;
+
+        let result = if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / rate).ceil() as u64)
+        };
+
+        bucket.full_at = now + Duration::from_secs_f64((capacity - bucket.tokens) / rate);
+
+        result
+    }
+}
+
+/// Расширение схемы, применяющее `RateLimiter` к корневым полям запроса.
+///
+/// Ошибка содержит время ожидания в расширении `retryAfter`,
+/// оно же передается в HTTP заголовке `Retry-After`.
+pub struct RateLimit {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimit {
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter }
+    }
+}
+
+impl ExtensionFactory for RateLimit {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(RateLimitExtension {
+            limiter: self.limiter.clone(),
+        })
+    }
+}
+
+struct RateLimitExtension {
+    limiter: Arc<RateLimiter>,
+}
+
+#[async_trait]
+impl Extension for RateLimitExtension {
+    async fn request(&self, ctx: &ExtensionContext<'_>, next: NextRequest<'_>) -> Response {
+        let mut response = next.run(ctx).await;
+
+        let retry_after = response
+            .errors
+            .iter()
+            .filter_map(|err| err.extensions.as_ref()?.get("retryAfter"))
+            .filter_map(|value| match value {
+                Value::Number(seconds) => seconds.as_u64(),
+                _ => None,
+            })
+            .max();
+
+        if let Some(seconds) = retry_after {
+            response.http_headers.insert("retry-after", seconds.into());
+        }
+
+        response
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        // Служебные поля интроспекции не учитываются
+        if info.path_node.parent.is_none() && !info.name.starts_with("__") {
+            let claims = ctx
+                .data_opt::<Result<Option<AccessClaims>, CustomError>>()
+                .and_then(|claims| claims.as_ref().ok())
+                .and_then(|claims| claims.as_ref());
+            let ip = ctx.data_opt::<ClientInfo>().and_then(|client| client.ip());
+
+            if let Err(seconds) = self.limiter.check(ip, claims, info.name) {
+                let err = limited_error(seconds);
+
+                return Err(ServerError {
+                    path: vec![PathSegment::Field(
+                        info.alias.unwrap_or(info.name).to_string(),
+                    )],
+                    extensions: err.extensions,
+                    ..ServerError::new(err.message, None)
+                });
+            }
+        }
+
+        next.run(ctx, info).await
+    }
+}
+
+fn limited_error(seconds: u64) -> GraphQLError {
+    let err = CustomError::new()
+        .kind(TooManyRequests)
+        .details(&format!("Try again in {} seconds", seconds))
+        .build();
+
+    GraphQLError::from(err).extend_with(|_, e| e.set("retryAfter", seconds))
+}
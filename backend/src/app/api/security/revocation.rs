@@ -0,0 +1,175 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use neo4rs::Graph;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::auth::AccessClaims;
+use crate::app::db::cypher;
+use crate::{app::core::error::CustomError, neo4j_result};
+
+type EmptyResult<'a> = Result<(), CustomError<'a>>;
+
+/// Хранилище отозванных Access токенов.
+///
+/// Отдельный токен отзывается по `jti`, все токены сессии по её
+/// идентификатору `sid`. Запись хранится до истечения срока токенов.
+///
+/// Все токены профиля отзываются увеличением счетчика поколений:
+/// токен, выпущенный с поколением меньше текущего, недействителен.
+#[async_trait]
+pub trait RevocationStoreT: Send + Sync {
+    async fn revoke(&self, id: String, exp: i64) -> EmptyResult;
+    async fn revoke_all(&self, sub: String) -> EmptyResult;
+    async fn generation(&self, sub: String) -> Result<i64, CustomError>;
+    async fn is_revoked(&self, claims: &AccessClaims) -> Result<bool, CustomError>;
+}
+
+/// Хранилище в памяти процесса.
+/// Подходит для локального окружения и запуска в одном экземпляре.
+#[derive(Default)]
+pub struct MemoryRevocationStore {
+    revoked: Mutex<HashMap<String, i64>>,
+    generations: Mutex<HashMap<String, i64>>,
+}
+
+impl MemoryRevocationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RevocationStoreT for MemoryRevocationStore {
+    async fn revoke(&self, id: String, exp: i64) -> EmptyResult {
+        let now = Utc::now().timestamp();
+        let mut revoked = self.revoked.lock().unwrap();
+
+        // Истекшие токены хранить больше не нужно
+        revoked.retain(|_, token_exp| *token_exp > now);
+        revoked.insert(id, exp);
+
+        Ok(())
+    }
+
+    async fn revoke_all(&self, sub: String) -> EmptyResult {
+        *self.generations.lock().unwrap().entry(sub).or_insert(0) += 1;
+
+        Ok(())
+    }
+
+    async fn generation(&self, sub: String) -> Result<i64, CustomError> {
+        Ok(*self.generations.lock().unwrap().get(&sub).unwrap_or(&0))
+    }
+
+    async fn is_revoked(&self, claims: &AccessClaims) -> Result<bool, CustomError> {
+        let revoked = {
+            let revoked = self.revoked.lock().unwrap();
+            revoked.contains_key(claims.jti()) || revoked.contains_key(claims.sid())
+        };
+        let generation = *self
+            .generations
+            .lock()
+            .unwrap()
+            .get(claims.sub())
+            .unwrap_or(&0);
+
+        Ok(revoked || claims.gen() < generation)
+    }
+}
+
+/// Хранилище в Neo4j.
+///
+/// Отозванные токены хранятся узлами :RevokedToken до истечения срока,
+/// поколение токенов хранится в свойстве `token_generation` узла :Profile.
+pub struct Neo4jRevocationStore {
+    neo: Arc<Graph>,
+}
+
+impl Neo4jRevocationStore {
+    pub fn new(neo4j: &Arc<Graph>) -> Self {
+        Self { neo: neo4j.clone() }
+    }
+}
+
+#[async_trait]
+impl RevocationStoreT for Neo4jRevocationStore {
+    async fn revoke(&self, id: String, exp: i64) -> EmptyResult {
+        let cleanup = cypher::query(
+            "
+                MATCH (r:RevokedToken) WHERE r.exp < $now
+                DELETE r
+            ",
+        )
+        .param("now", Utc::now().timestamp());
+
+        let query = cypher::query(
+            "
+                MERGE (r:RevokedToken {jti: $jti})
+                SET r.exp = $exp
+            ",
+        )
+        .param("jti", id)
+        .param("exp", exp);
+
+        neo4j_result!(self.neo.run(cleanup).await)?;
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    async fn revoke_all(&self, sub: String) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile) WHERE p.id = $id
+                SET p.token_generation = coalesce(p.token_generation, 0) + 1
+            ",
+        )
+        .param("id", sub);
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    async fn generation(&self, sub: String) -> Result<i64, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile) WHERE p.id = $id
+                RETURN coalesce(p.token_generation, 0) AS generation
+            ",
+        )
+        .param("id", sub);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        if let Ok(Some(row)) = result.next().await {
+            return Ok(row.get::<i64>("generation").unwrap_or(0));
+        }
+
+        Ok(0)
+    }
+
+    async fn is_revoked(&self, claims: &AccessClaims) -> Result<bool, CustomError> {
+        let query = cypher::query(
+            "
+                OPTIONAL MATCH (p:Profile) WHERE p.id = $id
+                OPTIONAL MATCH (r:RevokedToken) WHERE r.jti = $jti OR r.jti = $sid
+                RETURN count(r) > 0 AS revoked, coalesce(p.token_generation, 0) AS generation
+            ",
+        )
+        .param("jti", claims.jti().to_string())
+        .param("sid", claims.sid().to_string())
+        .param("id", claims.sub().to_string());
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        if let Ok(Some(row)) = result.next().await {
+            let revoked = row.get::<bool>("revoked").unwrap_or(false);
+            let generation = row.get::<i64>("generation").unwrap_or(0);
+
+            return Ok(revoked || claims.gen() < generation);
+        }
+
+        Ok(false)
+    }
+}
@@ -0,0 +1,63 @@
+use async_graphql::Enum;
+use strum_macros::{Display, EnumString};
+
+use crate::model::profile::profile_model::Permission;
+
+/// Именованная область доступа, передаваемая в Access токене.
+///
+/// Роль определяет, кем является владелец токена, а области
+/// определяют, какие действия разрешены конкретному токену.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Enum, Display, EnumString)]
+pub enum Scope {
+    #[serde(rename = "profile:read")]
+    #[strum(serialize = "profile:read")]
+    ProfileRead,
+
+    #[serde(rename = "profile:write")]
+    #[strum(serialize = "profile:write")]
+    ProfileWrite,
+
+    #[serde(rename = "chat:read")]
+    #[strum(serialize = "chat:read")]
+    ChatRead,
+
+    #[serde(rename = "chat:write")]
+    #[strum(serialize = "chat:write")]
+    ChatWrite,
+
+    /// Управление API ключами, для самих ключей выдается явно
+    #[serde(rename = "api_key:write")]
+    #[strum(serialize = "api_key:write")]
+    ApiKeyWrite,
+
+    #[serde(rename = "admin")]
+    #[strum(serialize = "admin")]
+    Admin,
+}
+
+impl Scope {
+    /// Области доступа, выдаваемые при входе профилю с указанной ролью.
+    /// Гостевые профили областей не получают, как и до появления областей
+    /// чтение профилей им недоступно.
+    pub fn for_permission(permission: Permission) -> Vec<Scope> {
+        let mut scopes = match permission {
+            Permission::Guest => vec![],
+            _ => vec![
+                Scope::ProfileRead,
+                Scope::ProfileWrite,
+                Scope::ChatRead,
+                Scope::ChatWrite,
+            ],
+        };
+
+        if permission.includes(Permission::Developer) {
+            scopes.push(Scope::ApiKeyWrite);
+        }
+
+        if permission.includes(Permission::Admin) {
+            scopes.push(Scope::Admin);
+        }
+
+        scopes
+    }
+}
@@ -0,0 +1,101 @@
+use actix_web::HttpRequest;
+use async_graphql::{Data, Error as GraphQLError};
+use chrono::Utc;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+use super::auth::{authenticate, AccessClaims};
+use crate::app::core::{context::Context as AppContext, error::CustomError};
+
+/// Аутентификация WebSocket соединения GraphQL подписок.
+///
+/// Клиент передает `Authorization` или `X-Api-Key` в полезной нагрузке
+/// сообщения `connection_init`. Если их там нет, используются заголовки
+/// запроса на открытие соединения. Полезная нагрузка токена помещается
+/// в контекст соединения так же, как для HTTP запросов.
+///
+/// Соединение с недействительным токеном отклоняется, соединение
+/// с истекшим токеном закрывается.
+#[derive(Clone)]
+pub struct ConnectionAuth {
+    app_ctx: Arc<AppContext>,
+    authorization: Option<String>,
+    api_key: Option<String>,
+    expiry: Arc<Mutex<Option<oneshot::Sender<i64>>>>,
+}
+
+impl ConnectionAuth {
+    /// Возвращает обработчик `connection_init` и future,
+    /// которое завершается по истечении срока токена соединения.
+    pub fn new(
+        app_ctx: Arc<AppContext>,
+        http_request: &HttpRequest,
+    ) -> (Self, impl Future<Output = ()>) {
+        let (sender, receiver) = oneshot::channel::<i64>();
+
+        let header = |name: &str| {
+            http_request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+
+        let auth = Self {
+            app_ctx,
+            authorization: header("Authorization"),
+            api_key: header("X-Api-Key"),
+            expiry: Arc::new(Mutex::new(Some(sender))),
+        };
+
+        let expired = async move {
+            match receiver.await {
+                Ok(exp) => {
+                    let left = (exp - Utc::now().timestamp()).max(0) as u64;
+                    tokio::time::sleep(std::time::Duration::from_secs(left)).await;
+                }
+                // Соединение без токена не ограничено по времени
+                Err(_) => std::future::pending::<()>().await,
+            }
+        };
+
+        (auth, expired)
+    }
+
+    /// Обработчик сообщения `connection_init`
+    pub fn on_connection_init(
+        &self,
+        payload: serde_json::Value,
+    ) -> impl Future<Output = async_graphql::Result<Data>> + Send + 'static {
+        let auth = self.clone();
+
+        async move {
+            let field = |name: &str| {
+                payload
+                    .get(name)
+                    .and_then(|value| value.as_str())
+                    .map(|value| value.to_string())
+            };
+
+            let authorization = field("Authorization").or(auth.authorization);
+            let api_key = field("X-Api-Key").or(auth.api_key);
+
+            let claims: Result<Option<AccessClaims>, CustomError> =
+                authenticate(&auth.app_ctx, authorization.as_deref(), api_key.as_deref()).await;
+
+            let claims = claims.map_err(GraphQLError::from)?;
+
+            if let Some(claims) = &claims {
+                if let Some(sender) = auth.expiry.lock().unwrap().take() {
+                    let _ = sender.send(claims.exp());
+                }
+            }
+
+            let mut data = Data::default();
+            data.insert(Ok::<_, CustomError>(claims));
+
+            Ok(data)
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::app::core::error::CustomError;
+use crate::model::profile::{profile_model::Suspension, profile_repository::ProfileRepositoryT};
+
+/// Срок хранения блокировки профиля в кеше в секундах
+const CACHE_SECONDS: i64 = 30;
+
+/// Кеш блокировок профилей для проверки каждого запроса.
+///
+/// Блокировка читается из базы не чаще раза в `CACHE_SECONDS`
+/// для профиля. Блокировка и разблокировка администратором
+/// сбрасывают запись сразу, на других экземплярах сервера
+/// изменение применяется по истечении срока.
+pub struct SuspensionCache {
+    entries: Mutex<HashMap<String, (Option<Suspension>, i64)>>,
+}
+
+impl Default for SuspensionCache {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl SuspensionCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Действующая блокировка профиля
+    pub async fn get<'a>(
+        &self,
+        profile_service: &dyn ProfileRepositoryT,
+        profile_id: &str,
+    ) -> Result<Option<Suspension>, CustomError<'a>> {
+        let now = Utc::now().timestamp();
+
+        let cached = self.entries.lock().unwrap().get(profile_id).cloned();
+
+        if let Some((suspension, fetched_at)) = cached {
+            if now - fetched_at < CACHE_SECONDS {
+                return Ok(suspension.filter(|suspension| suspension.is_active()));
+            }
+        }
+
+        let suspension = profile_service
+            .get_suspension(profile_id.to_string())
+            .await
+            .map_err(|err| crate::internal!(&err.details().unwrap_or_default()))?;
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (_, fetched_at)| now - *fetched_at < CACHE_SECONDS);
+        entries.insert(profile_id.to_string(), (suspension.clone(), now));
+
+        Ok(suspension)
+    }
+
+    /// Сброс записи после изменения блокировки профиля
+    pub fn invalidate(&self, profile_id: &str) {
+        self.entries.lock().unwrap().remove(profile_id);
+    }
+}
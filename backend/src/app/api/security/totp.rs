@@ -0,0 +1,135 @@
+use chrono::Utc;
+use rand::Rng;
+use ring::{constant_time, hmac};
+
+/// Длительность шага в секундах
+const STEP: i64 = 30;
+/// Количество цифр в коде
+const DIGITS: u32 = 6;
+/// Допустимое расхождение часов клиента в шагах
+const SKEW: i64 = 1;
+/// Издатель, отображаемый в приложении-аутентификаторе
+const ISSUER: &str = "langbro";
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Генерация секрета длиной 160 бит в кодировке Base32
+pub fn generate_secret() -> String {
+    let secret: [u8; 20] = rand::thread_rng().gen();
+    base32_encode(&secret)
+}
+
+/// Ссылка `otpauth://` для добавления секрета в приложение-аутентификатор
+pub fn otpauth_uri(secret: &str, account: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+        issuer = percent_encode(ISSUER),
+        account = percent_encode(account),
+        secret = secret,
+        digits = DIGITS,
+        period = STEP
+    )
+}
+
+/// Проверка кода по RFC 6238.
+///
+/// Возвращает шаг, для которого код верен. Шаги не позже `last_step`
+/// не принимаются, чтобы однажды использованный код нельзя было
+/// предъявить повторно.
+pub fn verify(secret: &str, code: &str, last_step: i64) -> Option<i64> {
+    let secret = base32_decode(secret)?;
+    let current = Utc::now().timestamp() / STEP;
+
+    (current - SKEW..=current + SKEW)
+        .filter(|step| *step > last_step)
+        .find(|step| {
+            constant_time::verify_slices_are_equal(
+                code_at(&secret, *step).as_bytes(),
+                code.as_bytes(),
+            )
+            .is_ok()
+        })
+}
+
+/// Вычисление кода HOTP (RFC 4226) для указанного шага
+fn code_at(secret: &[u8], step: i64) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret);
+    let tag = hmac::sign(&key, &(step as u64).to_be_bytes());
+    let hash = tag.as_ref();
+
+    // Динамическое усечение
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+/// Кодирование Base32 (RFC 4648) без выравнивания
+fn base32_encode(data: &[u8]) -> String {
+    let mut output = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    output
+}
+
+fn base32_decode(data: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for c in data.trim_end_matches('=').bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|a| *a == c.to_ascii_uppercase())?;
+
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(output)
+}
+
+/// Процентное кодирование (RFC 3986) всех символов, кроме незарезервированных
+fn percent_encode(data: &str) -> String {
+    let mut output = String::new();
+
+    for byte in data.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                output.push(byte as char)
+            }
+            _ => output.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    output
+}
@@ -3,21 +3,92 @@ use neo4rs::Graph;
 use std::sync::Arc;
 
 use crate::{
+    app::api::graphql::limits::QUERY_BUDGETS,
+    app::api::security::{
+        keys::KEY_STORE,
+        lockout::LoginLockout,
+        oidc::{OidcClient, OIDC_PROVIDERS},
+        password::BREACHED_PASSWORDS,
+        pow::ProofOfWork,
+        rate_limit::{RateLimiter, RATE_LIMITS},
+        revocation::{MemoryRevocationStore, Neo4jRevocationStore, RevocationStoreT},
+        suspension::SuspensionCache,
+    },
     app::db::neo4j,
+    app::mailer::{outbox::OutboxMailer, smtp::SmtpMailer, MailerT},
+    model::api_key::api_key_repository::{ApiKeyRepository, ApiKeyRepositoryT},
+    model::audit::audit_repository::{AuditRepository, AuditRepositoryT},
+    model::identity::identity_repository::{IdentityRepository, IdentityRepositoryT},
+    model::partner::partner_repository::{PartnerRepository, PartnerRepositoryT},
+    model::profile::profile_model::DELETION_GRACE_DAYS,
     model::profile::profile_repository::{ProfileRepository, ProfileRepositoryT},
+    model::session::session_repository::{SessionRepository, SessionRepositoryT},
+    model::suggestion::suggestion_repository::{SuggestionRepository, SuggestionRepositoryT},
+    model::token::token_repository::{TokenRepository, TokenRepositoryT},
+    model::two_factor::two_factor_repository::{TwoFactorRepository, TwoFactorRepositoryT},
 };
 
+#[derive(Clone)]
 pub struct Context {
     pub neodb: Arc<Graph>,
     pub profile_service: Arc<dyn ProfileRepositoryT>,
+    pub token_service: Arc<dyn TokenRepositoryT>,
+    pub session_service: Arc<dyn SessionRepositoryT>,
+    pub two_factor_service: Arc<dyn TwoFactorRepositoryT>,
+    pub identity_service: Arc<dyn IdentityRepositoryT>,
+    pub api_key_service: Arc<dyn ApiKeyRepositoryT>,
+    pub audit_service: Arc<dyn AuditRepositoryT>,
+    pub partner_service: Arc<dyn PartnerRepositoryT>,
+    pub suggestion_service: Arc<dyn SuggestionRepositoryT>,
+    pub login_lockout: Arc<LoginLockout>,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub proof_of_work: Arc<ProofOfWork>,
+    pub revocation_store: Arc<dyn RevocationStoreT>,
+    pub suspension_cache: Arc<SuspensionCache>,
+    pub mailer: Arc<dyn MailerT>,
+    pub oidc_client: Arc<OidcClient>,
 }
 
 impl Context {
     pub async fn init() -> Result<Self> {
+        // Ключи подписи загружаются при старте, чтобы ошибка
+        // в конфигурации не проявилась только на первом запросе
+        lazy_static::initialize(&KEY_STORE);
+        lazy_static::initialize(&OIDC_PROVIDERS);
+        lazy_static::initialize(&BREACHED_PASSWORDS);
+        lazy_static::initialize(&RATE_LIMITS);
+        lazy_static::initialize(&QUERY_BUDGETS);
+        lazy_static::initialize(&DELETION_GRACE_DAYS);
+
         let neodb = Arc::new(neo4j::connect().await?);
 
+        let revocation_store: Arc<dyn RevocationStoreT> = match dotenv!("TOKEN_REVOCATION_STORE") {
+            "memory" => Arc::new(MemoryRevocationStore::new()),
+            _ => Arc::new(Neo4jRevocationStore::new(&neodb)),
+        };
+
+        let mailer: Arc<dyn MailerT> = match dotenv!("MAILER") {
+            "outbox" => Arc::new(OutboxMailer::new(dotenv!("MAIL_OUTBOX_DIR"))),
+            _ => Arc::new(SmtpMailer::new()?),
+        };
+
         Ok(Self {
             profile_service: Arc::new(ProfileRepository::new(&neodb)),
+            token_service: Arc::new(TokenRepository::new(&neodb)),
+            session_service: Arc::new(SessionRepository::new(&neodb)),
+            two_factor_service: Arc::new(TwoFactorRepository::new(&neodb)),
+            identity_service: Arc::new(IdentityRepository::new(&neodb)),
+            api_key_service: Arc::new(ApiKeyRepository::new(&neodb)),
+            audit_service: Arc::new(AuditRepository::new(&neodb)),
+            partner_service: Arc::new(PartnerRepository::new(&neodb)),
+            suggestion_service: Arc::new(SuggestionRepository::new(&neodb)),
+            login_lockout: Arc::new(LoginLockout::new()),
+            rate_limiter: Arc::new(RateLimiter::new()),
+            proof_of_work: Arc::new(ProofOfWork::new()),
+            revocation_store,
+            suspension_cache: Arc::new(SuspensionCache::new()),
+            mailer,
+            oidc_client: Arc::new(OidcClient::new()),
             neodb,
         })
     }
@@ -1,8 +1,9 @@
 use actix_web::http::header::ToStrError;
 use async_graphql::{Error as GraphQLError, ErrorExtensions as GraphQLErrorExtensions};
+use std::collections::HashMap;
 use strum::ParseError;
 use thiserror::Error;
-use validator::ValidationErrors;
+use validator::{ValidationError, ValidationErrors};
 
 #[macro_export]
 macro_rules! neo4j_result {
@@ -62,15 +63,59 @@ pub enum CustomErrorKind<'a> {
     #[error("The passed token is not valid")]
     TokenInvalid,
 
+    #[error("Invalid username or password")]
+    InvalidCredentials,
+
+    #[error("Account is temporarily locked due to too many failed login attempts")]
+    AccountLocked,
+
+    #[error("Account is suspended")]
+    AccountSuspended,
+
+    #[error("Too many requests")]
+    TooManyRequests,
+
+    #[error("Query exceeds the allowed depth or complexity")]
+    QueryTooComplex,
+
     #[error("An unknown error has occurred")]
     Unknown,
 }
 
+/// Нарушение правила валидации поля входных данных
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    field: String,
+    code: String,
+    message: Option<String>,
+    params: HashMap<String, serde_json::Value>,
+}
+
+impl Violation {
+    fn new(field: &str, err: &ValidationError) -> Self {
+        Self {
+            field: field.to_string(),
+            code: err.code.to_string(),
+            message: err.message.as_ref().map(|message| message.to_string()),
+            // `validator` добавляет проверяемое значение в параметр `value`,
+            // возвращать его клиенту нельзя: это может быть пароль
+            params: err
+                .params
+                .iter()
+                .filter(|(name, _)| *name != "value")
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct CustomError<'a> {
     message: String,
     details: Option<String>,
     kind: CustomErrorKind<'a>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    violations: Vec<Violation>,
 }
 
 impl<'a> CustomError<'a> {
@@ -90,6 +135,10 @@ impl<'a> CustomError<'a> {
         self.kind.clone()
     }
 
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
     pub fn serde_kind(&self) -> &str {
         match self.kind {
             CustomErrorKind::NotFound(_) => "NOT_FOUND",
@@ -99,6 +148,11 @@ impl<'a> CustomError<'a> {
             CustomErrorKind::TokenExpired => "TOKEN_EXPIRED",
             CustomErrorKind::TokenMissing => "TOKEN_MISSING",
             CustomErrorKind::TokenInvalid => "TOKEN_INVALID",
+            CustomErrorKind::InvalidCredentials => "INVALID_CREDENTIALS",
+            CustomErrorKind::AccountLocked => "ACCOUNT_LOCKED",
+            CustomErrorKind::AccountSuspended => "ACCOUNT_SUSPENDED",
+            CustomErrorKind::TooManyRequests => "TOO_MANY_REQUESTS",
+            CustomErrorKind::QueryTooComplex => "QUERY_TOO_COMPLEX",
             CustomErrorKind::Unknown => "UNKNOWN",
         }
     }
@@ -124,13 +178,26 @@ impl<'a> From<ParseError> for CustomError<'a> {
 
 impl<'a> From<ValidationErrors> for CustomError<'a> {
     fn from(err: ValidationErrors) -> Self {
-        unprocessable!("data", Some(err.to_string()))
+        let violations = err
+            .field_errors()
+            .into_iter()
+            .flat_map(|(field, errors)| {
+                errors.iter().map(move |error| Violation::new(field, error))
+            })
+            .collect();
+
+        CustomError::new()
+            .kind(CustomErrorKind::Unprocessable("data"))
+            .details(&err.to_string())
+            .violations(violations)
+            .build()
     }
 }
 
 pub struct CustomErrorBuilder<'a> {
     details: Option<String>,
     kind: CustomErrorKind<'a>,
+    violations: Vec<Violation>,
 }
 
 impl<'a> Default for CustomErrorBuilder<'a> {
@@ -138,6 +205,7 @@ impl<'a> Default for CustomErrorBuilder<'a> {
         Self {
             details: None,
             kind: CustomErrorKind::Internal,
+            violations: Vec::new(),
         }
     }
 }
@@ -171,20 +239,37 @@ impl<'a> CustomErrorBuilder<'a> {
         }
     }
 
+    pub fn violations(mut self, violations: Vec<Violation>) -> Self {
+        self.violations = violations;
+
+        self
+    }
+
     pub fn build(self) -> CustomError<'a> {
         CustomError {
             message: self.kind.to_string(),
             details: self.details,
             kind: self.kind,
+            violations: self.violations,
         }
     }
 }
 
 impl<'a> From<CustomError<'a>> for GraphQLError {
     fn from(err: CustomError) -> Self {
+        let violations = match err.violations.is_empty() {
+            true => None,
+            false => async_graphql::to_value(&err.violations).ok(),
+        };
+
         GraphQLError::new(&err.message)
             .extend_with(|_, e| e.set("kind", err.serde_kind()))
             .extend_with(|_, e| e.set("details", err.details.unwrap_or("".to_string())))
+            .extend_with(|_, e| {
+                if let Some(violations) = violations {
+                    e.set("violations", violations)
+                }
+            })
     }
 }
 
@@ -0,0 +1,87 @@
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::context::Context;
+use crate::model::audit::{
+    audit_model::{AuditEntry, AuditEvent},
+    audit_repository::AuditRepositoryT,
+};
+use crate::model::profile::profile_repository::ProfileRepositoryT;
+
+/// Интервал пересчета популярности профилей
+const POPULARITY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Интервал удаления профилей с истекшим сроком ожидания
+const DELETION_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Запуск периодических задач сервера.
+///
+/// Первый запуск каждой задачи происходит сразу после старта.
+pub fn spawn(ctx: &Context) {
+    let suggestion_service = ctx.suggestion_service.clone();
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POPULARITY_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = suggestion_service.update_popularity().await {
+                log::error!("Failed to update profile popularity: {}", err.message());
+            }
+        }
+    });
+
+    let profile_service = ctx.profile_service.clone();
+    let audit_service = ctx.audit_service.clone();
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DELETION_INTERVAL);
+
+        loop {
+            interval.tick().await;
+            delete_expired_profiles(&profile_service, &audit_service).await;
+        }
+    });
+}
+
+/// Окончательное удаление профилей, срок ожидания удаления которых истек
+async fn delete_expired_profiles(
+    profile_service: &Arc<dyn ProfileRepositoryT>,
+    audit_service: &Arc<dyn AuditRepositoryT>,
+) {
+    let ids = match profile_service
+        .get_due_deletions(Utc::now().timestamp())
+        .await
+    {
+        Ok(ids) => ids,
+        Err(err) => {
+            log::error!(
+                "Failed to load profiles due for deletion: {}",
+                err.message()
+            );
+            return;
+        }
+    };
+
+    for id in ids {
+        // Профиль не удаляется, если вход успел отменить удаление
+        match profile_service.delete(id.clone(), true).await {
+            Ok(true) => {
+                let entry = AuditEntry::new(AuditEvent::ProfileDeleted)
+                    .target(&id)
+                    .details("grace period expired");
+
+                if let Err(err) = audit_service.record(&entry).await {
+                    log::error!(
+                        "Failed to record audit event {}: {}",
+                        AuditEvent::ProfileDeleted,
+                        err.message()
+                    );
+                }
+            }
+            Ok(false) => (),
+            Err(err) => log::error!("Failed to delete profile {}: {}", id, err.message()),
+        }
+    }
+}
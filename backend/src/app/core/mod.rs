@@ -1,2 +1,3 @@
 pub mod error;
-pub mod context;
\ No newline at end of file
+pub mod context;
+pub mod jobs;
\ No newline at end of file
@@ -0,0 +1,153 @@
+use std::fmt;
+use strum_macros::EnumString;
+
+use crate::model::profile::profile_model::Permission;
+
+/// Метки узлов, которые разрешено подставлять в текст запроса
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Label {
+    Profile,
+    Language,
+    Unverified,
+    Suspended,
+    PendingDeletion,
+    Guest,
+    User,
+    Developer,
+    Admin,
+}
+
+impl Label {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Label::Profile => "Profile",
+            Label::Language => "Language",
+            Label::Unverified => "Unverified",
+            Label::Suspended => "Suspended",
+            Label::PendingDeletion => "PendingDeletion",
+            Label::Guest => "Guest",
+            Label::User => "User",
+            Label::Developer => "Developer",
+            Label::Admin => "Admin",
+        }
+    }
+
+    /// Метки всех ролей профиля
+    pub const ROLES: [Label; 4] = [Label::Guest, Label::User, Label::Developer, Label::Admin];
+}
+
+/// Роль профиля хранится меткой узла :Profile
+impl From<Permission> for Label {
+    fn from(permission: Permission) -> Self {
+        match permission {
+            Permission::Guest => Label::Guest,
+            Permission::User => Label::User,
+            Permission::Developer => Label::Developer,
+            Permission::Admin => Label::Admin,
+        }
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Типы связей, которые разрешено подставлять в текст запроса
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum RelType {
+    #[strum(serialize = "NATIVE_SPEAKER")]
+    NativeSpeaker,
+    #[strum(serialize = "STUDIED")]
+    Studied,
+    #[strum(serialize = "SUBSCRIBE")]
+    Subscribe,
+}
+
+impl RelType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelType::NativeSpeaker => "NATIVE_SPEAKER",
+            RelType::Studied => "STUDIED",
+            RelType::Subscribe => "SUBSCRIBE",
+        }
+    }
+}
+
+impl fmt::Display for RelType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Запрос со статическим текстом.
+///
+/// Все репозитории создают запросы через этот модуль: текст принимается
+/// только как `&'static str`, поэтому собрать его из пользовательских
+/// данных через `format!` не получится. Данные передаются параметрами.
+pub fn query(text: &'static str) -> neo4rs::Query {
+    neo4rs::query(text)
+}
+
+/// Шаблон запроса с метками и типами связей.
+///
+/// Место подстановки обозначается в тексте как `{name}`,
+/// подставить можно только значения `Label` и `RelType`.
+pub struct Cypher {
+    text: &'static str,
+    substitutions: Vec<(&'static str, String)>,
+}
+
+impl Cypher {
+    pub fn new(text: &'static str) -> Self {
+        Self {
+            text,
+            substitutions: Vec::new(),
+        }
+    }
+
+    /// Подставить метку узла
+    pub fn label(self, name: &'static str, label: Label) -> Self {
+        self.substitute(name, label.as_str().to_string())
+    }
+
+    /// Подставить несколько меток узла через двоеточие, например `A:B:C`
+    pub fn labels(self, name: &'static str, labels: &[Label]) -> Self {
+        let labels = labels
+            .iter()
+            .map(|label| label.as_str())
+            .collect::<Vec<_>>()
+            .join(":");
+
+        self.substitute(name, labels)
+    }
+
+    /// Подставить тип связи
+    pub fn rel(self, name: &'static str, rel: RelType) -> Self {
+        self.substitute(name, rel.as_str().to_string())
+    }
+
+    pub fn build(self) -> neo4rs::Query {
+        let text = self
+            .substitutions
+            .iter()
+            .fold(self.text.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            });
+
+        neo4rs::Query::new(text)
+    }
+
+    fn substitute(mut self, name: &'static str, value: String) -> Self {
+        debug_assert!(
+            self.text.contains(&format!("{{{}}}", name)),
+            "Placeholder `{}` is missing in query",
+            name
+        );
+
+        self.substitutions.push((name, value));
+        self
+    }
+}
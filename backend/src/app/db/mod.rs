@@ -1 +1,2 @@
-pub mod neo4j;
\ No newline at end of file
+pub mod cypher;
+pub mod neo4j;
@@ -1,8 +1,6 @@
 use anyhow::Result;
 use neo4rs::Graph as Neo4jGraphDB;
 
-pub static NULL: &'static str = "NULL";
-
 pub async fn connect() -> Result<Neo4jGraphDB> {
     let config = neo4rs::config()
         .uri(&format!(
@@ -0,0 +1,23 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+pub mod outbox;
+pub mod smtp;
+pub mod templates;
+
+/// Письмо для отправки
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Отправка писем.
+///
+/// Реализация выбирается переменной окружения `MAILER`:
+/// `smtp` для отправки через SMTP сервер, `outbox` для
+/// сохранения писем в каталог при локальной разработке.
+#[async_trait]
+pub trait MailerT: Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<()>;
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use super::{Mail, MailerT};
+
+/// Сохранение писем в каталог вместо отправки.
+///
+/// Каждое письмо записывается отдельным файлом, что позволяет
+/// проверять отправленные ссылки при разработке и в тестах.
+pub struct OutboxMailer {
+    dir: PathBuf,
+}
+
+impl OutboxMailer {
+    pub fn new(dir: &str) -> Self {
+        Self {
+            dir: PathBuf::from(dir),
+        }
+    }
+}
+
+#[async_trait]
+impl MailerT for OutboxMailer {
+    async fn send(&self, mail: Mail) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+
+        let path = self.dir.join(format!(
+            "{}-{}.eml",
+            Utc::now().timestamp_millis(),
+            Uuid::new_v4()
+        ));
+        let content = format!(
+            "From: {}\nTo: {}\nSubject: {}\n\n{}\n",
+            dotenv!("MAIL_FROM"),
+            mail.to,
+            mail.subject,
+            mail.body
+        );
+
+        tokio::fs::write(path, content).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use super::{Mail, MailerT};
+
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    pub fn new() -> Result<Self> {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::relay(dotenv!("SMTP_HOST"))?
+            .port(dotenv!("SMTP_PORT").parse()?)
+            .credentials(Credentials::new(
+                dotenv!("SMTP_USER").to_string(),
+                dotenv!("SMTP_PASSWORD").to_string(),
+            ))
+            .build();
+
+        Ok(Self {
+            from: dotenv!("MAIL_FROM").parse()?,
+            transport,
+        })
+    }
+}
+
+#[async_trait]
+impl MailerT for SmtpMailer {
+    async fn send(&self, mail: Mail) -> Result<()> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(mail.to.parse()?)
+            .subject(mail.subject)
+            .body(mail.body)?;
+
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
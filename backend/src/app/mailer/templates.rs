@@ -0,0 +1,33 @@
+use super::Mail;
+
+/// Письмо со ссылкой подтверждения адреса электронной почты
+pub fn verify_email(to: &str, token: &str) -> Mail {
+    Mail {
+        to: to.to_string(),
+        subject: "Confirm your email address".to_string(),
+        body: format!(
+            "Welcome to langbro!\n\n\
+            To confirm your email address, follow the link:\n\
+            {}/verify-email?token={}\n\n\
+            If you did not register, just ignore this email.",
+            dotenv!("APP_URL"),
+            token
+        ),
+    }
+}
+
+/// Письмо со ссылкой сброса пароля
+pub fn reset_password(to: &str, token: &str) -> Mail {
+    Mail {
+        to: to.to_string(),
+        subject: "Reset your password".to_string(),
+        body: format!(
+            "To set a new password, follow the link:\n\
+            {}/reset-password?token={}\n\n\
+            The link can be used only once. If you did not request \
+            a password reset, just ignore this email.",
+            dotenv!("APP_URL"),
+            token
+        ),
+    }
+}
@@ -1,4 +1,5 @@
 pub mod api;
 pub mod core;
 pub mod db;
+pub mod mailer;
 pub mod utils;
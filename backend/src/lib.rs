@@ -13,13 +13,19 @@ extern crate serde_json;
 
 use actix_web::{guard, web, HttpRequest, HttpResponse, Result};
 use app::api::security;
+use async_graphql::futures_util::StreamExt;
 use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
-use async_graphql::Schema;
+use async_graphql::{Data, Schema};
 use async_graphql_actix_web::{GraphQLRequest, GraphQLSubscription};
 
 use app::api::graphql::AppSchema;
+use app::core::context::Context;
 
 pub fn configure_service(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/.well-known/jwks.json").route(web::get().to(jwks)));
+    cfg.service(
+        web::resource("/audit/export").route(web::get().to(model::audit::audit_export::export)),
+    );
     cfg.service(
         web::resource("/")
             .route(web::post().to(index))
@@ -33,22 +39,43 @@ pub fn configure_service(cfg: &mut web::ServiceConfig) {
 }
 
 async fn index(
+    ctx: web::Data<Context>,
     schema: web::Data<AppSchema>,
     http_req: HttpRequest,
     req: GraphQLRequest,
 ) -> async_graphql_actix_web::GraphQLResponse {
     let mut query = req.into_inner();
-    let getting_claims_result = security::auth::parse_auth(http_req);
+    query = query.data(security::client::ClientInfo::from_request(&http_req));
+    let getting_claims_result = security::auth::parse_auth(&ctx, &http_req).await;
     query = query.data(getting_claims_result);
     schema.execute(query).await.into()
 }
 
+/// WebSocket соединение GraphQL подписок.
+///
+/// Клиент аутентифицируется сообщением `connection_init`, по истечении
+/// срока токена входящий поток завершается и соединение закрывается.
 async fn index_ws(
+    ctx: web::Data<Context>,
     schema: web::Data<AppSchema>,
     req: HttpRequest,
     payload: web::Payload,
 ) -> Result<HttpResponse> {
-    GraphQLSubscription::new(Schema::clone(&*schema)).start(&req, payload)
+    let (auth, expired) = security::subscription::ConnectionAuth::new(ctx.into_inner(), &req);
+
+    let mut data = Data::default();
+    data.insert(security::client::ClientInfo::from_request(&req));
+
+    GraphQLSubscription::new(Schema::clone(&*schema))
+        .with_data(data)
+        .on_connection_init(move |payload| auth.on_connection_init(payload))
+        .start(&req, payload.take_until(expired))
+}
+
+/// Открытые ключи подписи токенов.
+/// Позволяет другим сервисам проверять токены langbro самостоятельно.
+async fn jwks() -> HttpResponse {
+    HttpResponse::Ok().json(security::keys::KEY_STORE.jwks())
 }
 
 async fn index_playground() -> HttpResponse {
@@ -3,6 +3,7 @@ use anyhow::Result;
 use dotenv::dotenv;
 use langbro::app::api::graphql::build_schema_with_context;
 use langbro::app::core::context::Context;
+use langbro::app::core::jobs;
 use langbro::configure_service;
 
 #[tokio::main]
@@ -11,12 +12,16 @@ async fn main() -> Result<()> {
     pretty_env_logger::init();
 
     let ctx = Context::init().await?;
-    let schema = web::Data::new(build_schema_with_context(ctx));
+    jobs::spawn(&ctx);
+
+    let schema = web::Data::new(build_schema_with_context(ctx.clone()));
+    let ctx = web::Data::new(ctx);
 
     let server = HttpServer::new(move || {
         App::new()
             .configure(configure_service)
             .app_data(schema.clone())
+            .app_data(ctx.clone())
     })
     .bind("0.0.0.0:8080")?
     .run();
@@ -0,0 +1,3 @@
+lazy_static! {
+    pub static ref ERR_ADMIN__SELF_MANAGE: &'static str = "You can't change your own account";
+}
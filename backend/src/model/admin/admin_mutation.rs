@@ -0,0 +1,14 @@
+use async_graphql::InputObject;
+use validator::Validate;
+
+#[derive(Validate, Serialize, Deserialize, InputObject)]
+pub struct SuspendProfileInput {
+    pub(super) id: String,
+
+    #[validate(length(min = 1, max = 500, message = "Lenght is invalid"))]
+    pub(super) reason: String,
+
+    /// Срок блокировки в часах, без срока профиль блокируется бессрочно
+    #[validate(range(min = 1, message = "Duration is invalid"))]
+    pub(super) duration_hours: Option<u32>,
+}
@@ -0,0 +1,176 @@
+use async_graphql::{Context, Object, Result as GraphQLResult};
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::app::api::security::{
+    auth::{get_access_claims, AuthGuard},
+    scope::Scope,
+    suspension::SuspensionCache,
+};
+use crate::model::audit::audit_model::{audit, AuditEntry, AuditEvent};
+use crate::model::profile::{
+    profile_model::{Permission, Profile, Suspension, DELETION_GRACE_DAYS},
+    profile_repository::ProfileRepositoryT,
+};
+use crate::model::token::token_model::revoke_all_sessions;
+
+use super::{admin_error::ERR_ADMIN__SELF_MANAGE, admin_mutation::SuspendProfileInput};
+
+#[derive(Default)]
+pub struct AdminMutation;
+
+#[Object]
+impl<'a> AdminMutation {
+    /// Метод изменения роли профиля.
+    ///
+    /// Сессии профиля завершаются, чтобы новая роль
+    /// применилась сразу, а не после истечения токенов.
+    #[graphql(guard = "AuthGuard::scope(Scope::Admin)")]
+    async fn set_profile_permission(
+        &'a self,
+        ctx: &'a Context<'_>,
+        id: String,
+        permission: Permission,
+    ) -> GraphQLResult<&str> {
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let profile = managed_profile(ctx, id).await?;
+
+        profile_service
+            .set_permission(profile.id.to_string(), permission)
+            .await?;
+        revoke_all_sessions(ctx, profile.id.to_string()).await?;
+
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::PermissionChanged)
+                .target(&profile.id.to_string())
+                .details(&format!("{} -> {}", profile.permission, permission)),
+        )
+        .await;
+
+        Ok("OK")
+    }
+
+    /// Метод блокировки профиля с указанием причины.
+    ///
+    /// Без срока профиль блокируется бессрочно. Все сессии
+    /// профиля завершаются, вход до окончания блокировки запрещен.
+    #[graphql(guard = "AuthGuard::scope(Scope::Admin)")]
+    async fn suspend_profile(
+        &'a self,
+        ctx: &'a Context<'_>,
+        input: SuspendProfileInput,
+    ) -> GraphQLResult<&str> {
+        input.validate()?;
+
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let profile = managed_profile(ctx, input.id).await?;
+
+        let suspension = Suspension {
+            reason: input.reason,
+            until: input
+                .duration_hours
+                .map(|hours| (Utc::now() + Duration::hours(hours as i64)).timestamp()),
+        };
+
+        let entry = AuditEntry::new(AuditEvent::ProfileSuspended)
+            .target(&profile.id.to_string())
+            .details(&suspension.reason);
+
+        profile_service
+            .suspend(profile.id.to_string(), suspension)
+            .await?;
+        ctx.data::<Arc<SuspensionCache>>()?
+            .invalidate(&profile.id.to_string());
+        revoke_all_sessions(ctx, profile.id.to_string()).await?;
+
+        audit(ctx, entry).await;
+
+        Ok("OK")
+    }
+
+    /// Метод снятия блокировки профиля
+    #[graphql(guard = "AuthGuard::scope(Scope::Admin)")]
+    async fn unsuspend_profile(&'a self, ctx: &'a Context<'_>, id: String) -> GraphQLResult<&str> {
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let profile = managed_profile(ctx, id).await?;
+
+        profile_service.unsuspend(profile.id.to_string()).await?;
+        ctx.data::<Arc<SuspensionCache>>()?
+            .invalidate(&profile.id.to_string());
+
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::ProfileUnsuspended).target(&profile.id.to_string()),
+        )
+        .await;
+
+        Ok("OK")
+    }
+
+    /// Метод удаления профиля администратором.
+    ///
+    /// По умолчанию профиль удаляется так же, как через `deleteAccount`:
+    /// деактивируется и удаляется окончательно по истечении срока
+    /// ожидания, но вход профиля такое удаление не отменяет.
+    /// С `immediate` профиль удаляется сразу.
+    #[graphql(guard = "AuthGuard::scope(Scope::Admin)")]
+    async fn delete_profile(
+        &'a self,
+        ctx: &'a Context<'_>,
+        id: String,
+        #[graphql(default = false)] immediate: bool,
+    ) -> GraphQLResult<&str> {
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let profile = managed_profile(ctx, id).await?;
+        let profile_id = profile.id.to_string();
+
+        revoke_all_sessions(ctx, profile_id.clone()).await?;
+
+        if immediate {
+            profile_service.delete(profile_id.clone(), false).await?;
+
+            audit(
+                ctx,
+                AuditEntry::new(AuditEvent::ProfileDeleted).target(&profile_id),
+            )
+            .await;
+        } else {
+            let scheduled_at = (Utc::now() + Duration::days(*DELETION_GRACE_DAYS)).timestamp();
+
+            profile_service
+                .schedule_deletion(
+                    profile_id.clone(),
+                    scheduled_at,
+                    get_access_claims(ctx).sub().to_string(),
+                )
+                .await?;
+
+            audit(
+                ctx,
+                AuditEntry::new(AuditEvent::ProfileDeletionScheduled)
+                    .target(&profile_id)
+                    .details(&scheduled_at.to_string()),
+            )
+            .await;
+        }
+
+        Ok("OK")
+    }
+}
+
+/// Получение профиля, которым управляет администратор.
+/// Изменять собственный профиль запрещено, чтобы не лишиться доступа.
+async fn managed_profile(ctx: &Context<'_>, id: String) -> GraphQLResult<Profile> {
+    let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+    let access_claims = get_access_claims(ctx);
+
+    let profile = profile_service.get_data(id).await?;
+
+    if profile.id.to_string() == access_claims.sub() {
+        return Err(crate::unprocessable!("id", Some(ERR_ADMIN__SELF_MANAGE.to_string())).into());
+    }
+
+    Ok(profile)
+}
@@ -0,0 +1,3 @@
+pub mod admin_error;
+pub mod admin_mutation;
+pub mod admin_resolver;
@@ -0,0 +1,4 @@
+lazy_static! {
+    pub static ref ERR_API_KEY__SCOPE_NOT_ALLOWED: &'static str =
+        "Requested scope exceeds the scopes of the current token";
+}
@@ -0,0 +1,95 @@
+use async_graphql::Object;
+use rand::Rng;
+use uuid::Uuid;
+
+use crate::app::api::security::scope::Scope;
+
+/// Префикс, по которому ключ легко опознать в конфигурации и логах
+const KEY_PREFIX: &str = "lb_";
+/// Длина отображаемой части ключа
+const DISPLAY_PREFIX_LEN: usize = 10;
+
+/// API ключ для ботов и интеграций.
+///
+/// Хранится узлом :ApiKey, связанным с :Profile. Сам ключ не
+/// сохраняется, для поиска используется его SHA-256 хеш: ключ
+/// случаен и достаточно длинный, поэтому медленный хеш не нужен.
+pub struct ApiKey {
+    pub(crate) id: Uuid,
+    pub(super) name: String,
+    /// Начало ключа для отображения в списке
+    pub(super) prefix: String,
+    pub(crate) scopes: Vec<Scope>,
+    pub(super) created_at: i64,
+    pub(super) last_used_at: Option<i64>,
+}
+
+/// Генерация нового ключа.
+/// Возвращает ключ и отображаемый префикс.
+pub(super) fn generate_key() -> (String, String) {
+    let secret: [u8; 32] = rand::thread_rng().gen();
+    let key = format!(
+        "{}{}",
+        KEY_PREFIX,
+        base64::encode_config(secret, base64::URL_SAFE_NO_PAD)
+    );
+    let prefix = key[..DISPLAY_PREFIX_LEN].to_string();
+
+    (key, prefix)
+}
+
+/// Хеш ключа, по которому он хранится и ищется
+pub fn hash_key(key: &str) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, key.as_bytes());
+    base64::encode_config(digest.as_ref(), base64::URL_SAFE_NO_PAD)
+}
+
+#[Object]
+impl<'a> ApiKey {
+    async fn id(&'a self) -> String {
+        self.id.to_string()
+    }
+
+    async fn name(&'a self) -> &str {
+        &self.name
+    }
+
+    async fn prefix(&'a self) -> &str {
+        &self.prefix
+    }
+
+    async fn scopes(&'a self) -> &Vec<Scope> {
+        &self.scopes
+    }
+
+    async fn created_at(&'a self) -> i64 {
+        self.created_at
+    }
+
+    async fn last_used_at(&'a self) -> Option<i64> {
+        self.last_used_at
+    }
+}
+
+/// Созданный ключ. Значение `key` показывается только один раз.
+pub struct CreatedApiKey {
+    key: String,
+    api_key: ApiKey,
+}
+
+impl CreatedApiKey {
+    pub(super) fn new(key: String, api_key: ApiKey) -> Self {
+        Self { key, api_key }
+    }
+}
+
+#[Object]
+impl<'a> CreatedApiKey {
+    async fn key(&'a self) -> &str {
+        &self.key
+    }
+
+    async fn api_key(&'a self) -> &ApiKey {
+        &self.api_key
+    }
+}
@@ -0,0 +1,13 @@
+use async_graphql::InputObject;
+use validator::Validate;
+
+use crate::app::api::security::scope::Scope;
+
+#[derive(Validate, Serialize, Deserialize, InputObject)]
+pub struct CreateApiKeyInput {
+    #[validate(length(min = 1, max = 50, message = "Lenght is invalid"))]
+    pub(super) name: String,
+
+    #[validate(length(min = 1, message = "At least one scope is required"))]
+    pub(super) scopes: Vec<Scope>,
+}
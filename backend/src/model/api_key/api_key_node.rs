@@ -0,0 +1,30 @@
+use anyhow::Result;
+use neo4rs::Node;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::app::api::security::scope::Scope;
+use crate::app::core::error::CustomError;
+
+use super::api_key_model::ApiKey;
+
+/// Разделитель областей доступа в свойстве `scopes`
+pub(super) const SCOPES_SEPARATOR: &str = " ";
+
+impl<'a> ApiKey {
+    pub(super) fn parse_query_resp(knode: Node) -> Result<ApiKey, CustomError<'a>> {
+        Ok(ApiKey {
+            id: Uuid::parse_str(&knode.get::<String>("id").unwrap())?,
+            name: knode.get::<String>("name").unwrap(),
+            prefix: knode.get::<String>("prefix").unwrap(),
+            scopes: knode
+                .get::<String>("scopes")
+                .unwrap_or_default()
+                .split(SCOPES_SEPARATOR)
+                .filter_map(|scope| Scope::from_str(scope).ok())
+                .collect(),
+            created_at: knode.get::<i64>("created_at").unwrap(),
+            last_used_at: knode.get::<i64>("last_used_at"),
+        })
+    }
+}
@@ -0,0 +1,180 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use neo4rs::Graph;
+use std::sync::Arc;
+
+use crate::app::db::cypher;
+use crate::model::profile::profile_model::Permission;
+use crate::{app::core::error::CustomError, neo4j_result};
+
+use super::api_key_model::ApiKey;
+use super::api_key_node::SCOPES_SEPARATOR;
+
+/// Точность отметки времени использования ключа в секундах.
+/// Отметка обновляется не чаще, чтобы запросы с ключом не
+/// превращались в пишущие транзакции.
+const LAST_USED_PRECISION: i64 = 60;
+
+type EmptyResult<'a> = Result<(), CustomError<'a>>;
+
+#[async_trait]
+pub trait ApiKeyRepositoryT: Send + Sync {
+    async fn create(&self, profile_id: String, api_key: &ApiKey, hash: String) -> EmptyResult;
+    async fn delete(&self, profile_id: String, id: String) -> Result<bool, CustomError>;
+
+    async fn get_all(&self, profile_id: String) -> Result<Vec<ApiKey>, CustomError>;
+    async fn authenticate(
+        &self,
+        hash: String,
+    ) -> Result<Option<(ApiKey, String, Permission)>, CustomError>;
+}
+
+pub struct ApiKeyRepository {
+    neo: Arc<Graph>,
+}
+
+impl ApiKeyRepository {
+    pub fn new(neo4j: &Arc<Graph>) -> Self {
+        Self { neo: neo4j.clone() }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepositoryT for ApiKeyRepository {
+    /* ======================== MUTATIONS ======================== */
+
+    async fn create(&self, profile_id: String, api_key: &ApiKey, hash: String) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile) WHERE p.id = $profile_id
+                CREATE (p)-[:HAS_API_KEY]->(:ApiKey {
+                    id: $id,
+                    name: $name,
+                    prefix: $prefix,
+                    hash: $hash,
+                    scopes: $scopes,
+                    created_at: $created_at
+                })
+            ",
+        )
+        .param("profile_id", profile_id)
+        .param("id", api_key.id.to_string())
+        .param("name", api_key.name.clone())
+        .param("prefix", api_key.prefix.clone())
+        .param("hash", hash)
+        .param(
+            "scopes",
+            api_key
+                .scopes
+                .iter()
+                .map(|scope| scope.to_string())
+                .collect::<Vec<String>>()
+                .join(SCOPES_SEPARATOR),
+        )
+        .param("created_at", api_key.created_at);
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Удалить ключ профиля.
+    ///
+    /// Возвращает `false`, если у профиля нет такого ключа.
+    async fn delete(&self, profile_id: String, id: String) -> Result<bool, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_API_KEY]->(k:ApiKey)
+                WHERE p.id = $profile_id AND k.id = $id
+                DETACH DELETE k
+                RETURN count(*) AS deleted
+            ",
+        )
+        .param("profile_id", profile_id)
+        .param("id", id);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        if let Ok(Some(row)) = result.next().await {
+            return Ok(row.get::<i64>("deleted").unwrap_or(0) > 0);
+        }
+
+        Ok(false)
+    }
+
+    /* ======================== QUERYS ======================== */
+
+    async fn get_all(&self, profile_id: String) -> Result<Vec<ApiKey>, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_API_KEY]->(k:ApiKey)
+                WHERE p.id = $profile_id
+                RETURN k
+                ORDER BY k.created_at DESC
+            ",
+        )
+        .param("profile_id", profile_id);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+        let mut output: Vec<ApiKey> = Vec::new();
+
+        while let Ok(Some(row)) = result.next().await {
+            if let Some(knode) = row.get::<neo4rs::Node>("k") {
+                output.push(ApiKey::parse_query_resp(knode)?);
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Поиск ключа по хешу с отметкой времени использования.
+    ///
+    /// Возвращает ключ, идентификатор владельца и его текущую роль.
+    /// Ключи профиля, ожидающего удаления, не принимаются. Отметка
+    /// обновляется, только если она старше `LAST_USED_PRECISION`.
+    async fn authenticate(
+        &self,
+        hash: String,
+    ) -> Result<Option<(ApiKey, String, Permission)>, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_API_KEY]->(k:ApiKey)
+                WHERE k.hash = $hash AND NOT p:PendingDeletion
+                RETURN k, p
+            ",
+        )
+        .param("hash", hash);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        let (api_key, pnode) = match result.next().await {
+            Ok(Some(row)) => match (row.get::<neo4rs::Node>("k"), row.get::<neo4rs::Node>("p")) {
+                (Some(knode), Some(pnode)) => (ApiKey::parse_query_resp(knode)?, pnode),
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        let now = Utc::now().timestamp();
+        if api_key.last_used_at.map_or(true, |last_used_at| {
+            now - last_used_at >= LAST_USED_PRECISION
+        }) {
+            let query = cypher::query(
+                "
+                    MATCH (k:ApiKey) WHERE k.id = $id
+                    SET k.last_used_at = $now
+                ",
+            )
+            .param("id", api_key.id.to_string())
+            .param("now", now);
+
+            neo4j_result!(self.neo.run(query).await)?;
+        }
+
+        Ok(Some((
+            api_key,
+            pnode.get::<String>("id").unwrap(),
+            Permission::from_labels(&pnode.labels()),
+        )))
+    }
+}
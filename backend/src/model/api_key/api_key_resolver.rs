@@ -0,0 +1,135 @@
+use async_graphql::{Context, Object, Result as GraphQLResult};
+use chrono::Utc;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::app::api::graphql::limits::{DB_COST, LIST_SIZE};
+use crate::app::api::security::{
+    auth::{get_access_claims, AuthGuard},
+    scope::Scope,
+};
+use crate::model::audit::audit_model::{audit, AuditEntry, AuditEvent};
+use crate::model::profile::profile_model::Permission;
+
+use super::{
+    api_key_error::ERR_API_KEY__SCOPE_NOT_ALLOWED,
+    api_key_model::{generate_key, hash_key, ApiKey, CreatedApiKey},
+    api_key_mutation::CreateApiKeyInput,
+    api_key_repository::ApiKeyRepositoryT,
+};
+
+#[derive(Default)]
+pub struct ApiKeyMutation;
+
+#[Object]
+impl<'a> ApiKeyMutation {
+    /// Метод создания API ключа.
+    ///
+    /// Ключ возвращается только в ответе на этот запрос. Области
+    /// доступа ключа не могут превышать области текущего токена,
+    /// API ключом новый ключ создается только с областью `api_key:write`.
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::Developer).and(AuthGuard::session_or(Scope::ApiKeyWrite))"
+    )]
+    async fn create_api_key(
+        &'a self,
+        ctx: &'a Context<'_>,
+        input: CreateApiKeyInput,
+    ) -> GraphQLResult<CreatedApiKey> {
+        input.validate()?;
+
+        let api_key_service = ctx.data::<Arc<dyn ApiKeyRepositoryT>>()?;
+        let access_claims = get_access_claims(ctx);
+
+        if input
+            .scopes
+            .iter()
+            .any(|scope| !access_claims.has_scope(*scope))
+        {
+            return Err(crate::unprocessable!(
+                "scopes",
+                Some(ERR_API_KEY__SCOPE_NOT_ALLOWED.to_string())
+            )
+            .into());
+        }
+
+        let mut scopes: Vec<Scope> = Vec::new();
+        for scope in input.scopes {
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+
+        let (key, prefix) = generate_key();
+        let api_key = ApiKey {
+            id: Uuid::new_v4(),
+            name: input.name,
+            prefix,
+            scopes,
+            created_at: Utc::now().timestamp(),
+            last_used_at: None,
+        };
+
+        api_key_service
+            .create(access_claims.sub().to_string(), &api_key, hash_key(&key))
+            .await?;
+
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::ApiKeyCreated)
+                .target(access_claims.sub())
+                .details(&api_key.id.to_string()),
+        )
+        .await;
+
+        Ok(CreatedApiKey::new(key, api_key))
+    }
+
+    /// Метод отзыва API ключа
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::Developer).and(AuthGuard::session_or(Scope::ApiKeyWrite))"
+    )]
+    async fn revoke_api_key(&'a self, ctx: &'a Context<'_>, id: String) -> GraphQLResult<&str> {
+        let api_key_service = ctx.data::<Arc<dyn ApiKeyRepositoryT>>()?;
+        let access_claims = get_access_claims(ctx);
+
+        if !api_key_service
+            .delete(access_claims.sub().to_string(), id.clone())
+            .await?
+        {
+            return Err(crate::not_found!("api_key").into());
+        }
+
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::ApiKeyRevoked)
+                .target(access_claims.sub())
+                .details(&id),
+        )
+        .await;
+
+        Ok("OK")
+    }
+}
+
+#[derive(Default)]
+pub struct ApiKeyQuery;
+
+#[Object]
+impl<'a> ApiKeyQuery {
+    /// API ключи текущего профиля.
+    /// API ключом список доступен только с областью `api_key:write`.
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::Developer).and(AuthGuard::session_or(Scope::ApiKeyWrite))",
+        complexity = "DB_COST + LIST_SIZE * child_complexity"
+    )]
+    async fn list_api_keys(&'a self, ctx: &'a Context<'_>) -> GraphQLResult<Vec<ApiKey>> {
+        let api_key_service = ctx.data::<Arc<dyn ApiKeyRepositoryT>>()?;
+        let access_claims = get_access_claims(ctx);
+
+        Ok(api_key_service
+            .get_all(access_claims.sub().to_string())
+            .await?)
+    }
+}
@@ -0,0 +1,7 @@
+pub mod api_key_error;
+pub mod api_key_model;
+pub mod api_key_mutation;
+pub mod api_key_repository;
+pub mod api_key_resolver;
+
+mod api_key_node;
@@ -0,0 +1,108 @@
+use actix_web::error::{Error, ErrorInternalServerError};
+use actix_web::web::{self, Bytes};
+use actix_web::{HttpRequest, HttpResponse};
+use async_graphql::futures_util::stream;
+use std::sync::Arc;
+
+use crate::app::api::security::{auth::parse_auth, scope::Scope};
+use crate::app::core::context::Context;
+
+use super::{audit_mutation::AuditFilter, audit_repository::AuditRepositoryT};
+
+/// Количество записей, читаемых из графа за один запрос
+const EXPORT_BATCH_SIZE: usize = 1000;
+
+/// Выгрузка журнала аудита в формате JSON Lines.
+///
+/// Доступна с Access токеном или API ключом с областью `admin`,
+/// принимает те же фильтры, что и запрос `auditLog`. Журнал
+/// отправляется страницами по `EXPORT_BATCH_SIZE` записей,
+/// не собираясь в памяти целиком.
+pub async fn export(
+    ctx: web::Data<Context>,
+    http_req: HttpRequest,
+    filter: web::Query<AuditFilter>,
+) -> HttpResponse {
+    match parse_auth(&ctx, &http_req).await {
+        Ok(Some(claims)) if claims.has_scope(Scope::Admin) => {}
+        Ok(Some(_)) => return HttpResponse::Forbidden().finish(),
+        Ok(None) | Err(_) => return HttpResponse::Unauthorized().finish(),
+    }
+
+    let state = ExportState {
+        audit_service: ctx.audit_service.clone(),
+        filter: filter.into_inner(),
+        cursor: None,
+        done: false,
+    };
+
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .insert_header((
+            "Content-Disposition",
+            "attachment; filename=\"audit.jsonl\"",
+        ))
+        .streaming(stream::unfold(state, next_batch))
+}
+
+/// Состояние выгрузки между страницами журнала
+struct ExportState {
+    audit_service: Arc<dyn AuditRepositoryT>,
+    filter: AuditFilter,
+    cursor: Option<(i64, String)>,
+    done: bool,
+}
+
+/// Следующая страница журнала в формате JSON Lines.
+///
+/// Заголовки ответа к этому моменту уже отправлены, поэтому
+/// ошибка прерывает выгрузку, а не меняет статус ответа.
+async fn next_batch(mut state: ExportState) -> Option<(Result<Bytes, Error>, ExportState)> {
+    if state.done {
+        return None;
+    }
+
+    // Ошибка репозитория заимствует сервис, а не состояние выгрузки
+    let audit_service = state.audit_service.clone();
+
+    let entries = match audit_service
+        .find(&state.filter, state.cursor.clone(), EXPORT_BATCH_SIZE)
+        .await
+    {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::error!("Failed to export audit log: {}", err.message());
+            state.done = true;
+            return Some((Err(ErrorInternalServerError("Audit export failed")), state));
+        }
+    };
+
+    if entries.is_empty() {
+        return None;
+    }
+
+    let mut body = String::new();
+
+    for entry in &entries {
+        match serde_json::to_string(entry) {
+            Ok(line) => {
+                body.push_str(&line);
+                body.push('\n');
+            }
+            Err(err) => {
+                log::error!("Failed to serialize audit entry: {}", err);
+                state.done = true;
+                return Some((Err(ErrorInternalServerError("Audit export failed")), state));
+            }
+        }
+    }
+
+    match entries.last() {
+        Some(last) if entries.len() == EXPORT_BATCH_SIZE => {
+            state.cursor = Some((last.created_at, last.id.clone()));
+        }
+        _ => state.done = true,
+    }
+
+    Some((Ok(Bytes::from(body)), state))
+}
@@ -0,0 +1,133 @@
+use async_graphql::{Context, Enum, SimpleObject};
+use chrono::Utc;
+use std::sync::Arc;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::app::api::security::{auth::AccessClaims, client::ClientInfo};
+use crate::app::core::error::CustomError;
+
+use super::audit_repository::AuditRepositoryT;
+
+/// Количество записей на странице журнала по умолчанию
+pub(super) const AUDIT_PAGE_SIZE: usize = 20;
+/// Максимальное количество записей на странице журнала
+pub(super) const AUDIT_PAGE_MAX: usize = 100;
+
+/// Событие журнала аудита
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Enum, Display, EnumString)]
+pub enum AuditEvent {
+    Registration,
+    LoginSucceeded,
+    LoginFailed,
+    /// Вход отклонен блокировкой профиля или IP адреса
+    LoginLocked,
+    TokenRefreshed,
+    PasswordChanged,
+    PasswordReset,
+    ProfileEdited,
+    LanguageRemoved,
+    LanguageLevelChanged,
+    TwoFactorEnabled,
+    TwoFactorDisabled,
+    ApiKeyCreated,
+    ApiKeyRevoked,
+    SessionRevoked,
+    ProfileDeletionScheduled,
+    ProfileDeletionCancelled,
+    ProfileDeleted,
+    PermissionChanged,
+    ProfileSuspended,
+    ProfileUnsuspended,
+}
+
+/// Запись журнала аудита.
+///
+/// Хранится отдельным узлом :AuditEntry без связей с профилями,
+/// чтобы журнал сохранялся после удаления участников события.
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct AuditEntry {
+    pub(super) id: String,
+    pub(super) event: AuditEvent,
+    /// Профиль, выполнивший действие
+    pub(super) actor: Option<String>,
+    /// Профиль, над которым выполнено действие
+    pub(super) target: Option<String>,
+    pub(super) ip: Option<String>,
+    pub(super) user_agent: Option<String>,
+    /// Дополнительные сведения, например новая роль
+    pub(super) details: Option<String>,
+    pub(super) created_at: i64,
+}
+
+impl AuditEntry {
+    pub fn new(event: AuditEvent) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            event,
+            actor: None,
+            target: None,
+            ip: None,
+            user_agent: None,
+            details: None,
+            created_at: Utc::now().timestamp(),
+        }
+    }
+
+    pub fn actor(mut self, actor: &str) -> Self {
+        self.actor = Some(actor.to_string());
+        self
+    }
+
+    pub fn target(mut self, target: &str) -> Self {
+        self.target = Some(target.to_string());
+        self
+    }
+
+    pub fn details(mut self, details: &str) -> Self {
+        self.details = Some(details.to_string());
+        self
+    }
+
+    /// Курсор записи для постраничного вывода
+    pub(super) fn cursor(&self) -> String {
+        format!("{}:{}", self.created_at, self.id)
+    }
+
+    pub(super) fn parse_cursor(cursor: &str) -> Option<(i64, String)> {
+        let (created_at, id) = cursor.split_once(':')?;
+        Some((created_at.parse().ok()?, id.to_string()))
+    }
+}
+
+/// Запись события в журнал аудита.
+///
+/// IP адрес и User-Agent берутся из сведений о клиенте, а инициатор,
+/// если он не указан, из Access токена. Ошибка записи только
+/// логируется, чтобы недоступность журнала не прерывала операцию.
+pub(crate) async fn audit(ctx: &Context<'_>, mut entry: AuditEntry) {
+    if let Some(client) = ctx.data_opt::<ClientInfo>() {
+        entry.ip = client.ip().map(|ip| ip.to_string());
+        entry.user_agent = client.user_agent().map(|agent| agent.to_string());
+    }
+
+    if entry.actor.is_none() {
+        entry.actor = ctx
+            .data_opt::<Result<Option<AccessClaims>, CustomError>>()
+            .and_then(|claims| claims.as_ref().ok())
+            .and_then(|claims| claims.as_ref())
+            .map(|claims| claims.sub().to_string());
+    }
+
+    let result = match ctx.data::<Arc<dyn AuditRepositoryT>>() {
+        Ok(audit_service) => audit_service
+            .record(&entry)
+            .await
+            .map_err(|err| err.message()),
+        Err(err) => Err(err.message),
+    };
+
+    if let Err(err) = result {
+        log::error!("Failed to record audit event {}: {}", entry.event, err);
+    }
+}
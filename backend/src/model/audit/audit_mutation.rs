@@ -0,0 +1,17 @@
+use async_graphql::InputObject;
+
+use super::audit_model::AuditEvent;
+
+/// Фильтр журнала аудита.
+/// Используется как в GraphQL запросе, так и в параметрах выгрузки.
+#[derive(Debug, Default, Clone, Deserialize, InputObject)]
+pub struct AuditFilter {
+    pub event: Option<AuditEvent>,
+    pub actor: Option<String>,
+    pub target: Option<String>,
+    pub ip: Option<String>,
+    /// Начало периода, секунды Unix времени
+    pub from: Option<i64>,
+    /// Конец периода, секунды Unix времени
+    pub to: Option<i64>,
+}
@@ -0,0 +1,25 @@
+use anyhow::Result;
+use neo4rs::Node;
+use std::str::FromStr;
+
+use crate::app::core::error::CustomError;
+
+use super::audit_model::{AuditEntry, AuditEvent};
+
+impl<'a> AuditEntry {
+    pub(super) fn parse_query_resp(anode: Node) -> Result<AuditEntry, CustomError<'a>> {
+        // Отсутствующие значения хранятся пустыми строками
+        let optional = |key: &str| anode.get::<String>(key).filter(|value| !value.is_empty());
+
+        Ok(AuditEntry {
+            id: anode.get::<String>("id").unwrap(),
+            event: AuditEvent::from_str(&anode.get::<String>("event").unwrap())?,
+            actor: optional("actor"),
+            target: optional("target"),
+            ip: optional("ip"),
+            user_agent: optional("user_agent"),
+            details: optional("details"),
+            created_at: anode.get::<i64>("created_at").unwrap(),
+        })
+    }
+}
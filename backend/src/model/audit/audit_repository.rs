@@ -0,0 +1,123 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use neo4rs::Graph;
+use std::sync::Arc;
+
+use crate::app::db::cypher;
+use crate::{app::core::error::CustomError, neo4j_result};
+
+use super::{audit_model::AuditEntry, audit_mutation::AuditFilter};
+
+type EmptyResult<'a> = Result<(), CustomError<'a>>;
+
+#[async_trait]
+pub trait AuditRepositoryT: Send + Sync {
+    async fn record(&self, entry: &AuditEntry) -> EmptyResult;
+
+    async fn find(
+        &self,
+        filter: &AuditFilter,
+        after: Option<(i64, String)>,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>, CustomError>;
+}
+
+pub struct AuditRepository {
+    neo: Arc<Graph>,
+}
+
+impl AuditRepository {
+    pub fn new(neo4j: &Arc<Graph>) -> Self {
+        Self { neo: neo4j.clone() }
+    }
+}
+
+#[async_trait]
+impl AuditRepositoryT for AuditRepository {
+    /* ======================== MUTATIONS ======================== */
+
+    async fn record(&self, entry: &AuditEntry) -> EmptyResult {
+        let query = cypher::query(
+            "
+                CREATE (:AuditEntry {
+                    id: $id,
+                    event: $event,
+                    actor: $actor,
+                    target: $target,
+                    ip: $ip,
+                    user_agent: $user_agent,
+                    details: $details,
+                    created_at: $created_at
+                })
+            ",
+        )
+        .param("id", entry.id.clone())
+        .param("event", entry.event.to_string())
+        .param("actor", entry.actor.clone().unwrap_or_default())
+        .param("target", entry.target.clone().unwrap_or_default())
+        .param("ip", entry.ip.clone().unwrap_or_default())
+        .param("user_agent", entry.user_agent.clone().unwrap_or_default())
+        .param("details", entry.details.clone().unwrap_or_default())
+        .param("created_at", entry.created_at);
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /* ======================== QUERYS ======================== */
+
+    /// Записи журнала от новых к старым.
+    ///
+    /// Постраничный вывод по курсору `after` - времени и идентификатору
+    /// последней полученной записи, поэтому новые записи не сдвигают
+    /// уже полученные страницы.
+    async fn find(
+        &self,
+        filter: &AuditFilter,
+        after: Option<(i64, String)>,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>, CustomError> {
+        let (after_at, after_id) = after.unwrap_or((i64::MAX, String::new()));
+
+        let query = cypher::query(
+            "
+                MATCH (a:AuditEntry)
+                WHERE ($event = '' OR a.event = $event)
+                AND ($actor = '' OR a.actor = $actor)
+                AND ($target = '' OR a.target = $target)
+                AND ($ip = '' OR a.ip = $ip)
+                AND a.created_at >= $from AND a.created_at <= $to
+                AND (a.created_at < $after_at OR (a.created_at = $after_at AND a.id < $after_id))
+                RETURN a
+                ORDER BY a.created_at DESC, a.id DESC
+                LIMIT $limit
+            ",
+        )
+        .param(
+            "event",
+            filter
+                .event
+                .map(|event| event.to_string())
+                .unwrap_or_default(),
+        )
+        .param("actor", filter.actor.clone().unwrap_or_default())
+        .param("target", filter.target.clone().unwrap_or_default())
+        .param("ip", filter.ip.clone().unwrap_or_default())
+        .param("from", filter.from.unwrap_or(0))
+        .param("to", filter.to.unwrap_or(i64::MAX))
+        .param("after_at", after_at)
+        .param("after_id", after_id)
+        .param("limit", limit as i64);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+        let mut output: Vec<AuditEntry> = Vec::new();
+
+        while let Ok(Some(row)) = result.next().await {
+            if let Some(anode) = row.get::<neo4rs::Node>("a") {
+                output.push(AuditEntry::parse_query_resp(anode)?);
+            }
+        }
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,68 @@
+use async_graphql::connection::{query, Connection, Edge};
+use async_graphql::{Context, Error as GraphQLError, Object, Result as GraphQLResult};
+use std::sync::Arc;
+
+use crate::app::api::graphql::limits::{page_size, truncate_page, DB_COST};
+use crate::app::api::security::{auth::AuthGuard, scope::Scope};
+
+use super::{
+    audit_model::{AuditEntry, AUDIT_PAGE_MAX, AUDIT_PAGE_SIZE},
+    audit_mutation::AuditFilter,
+    audit_repository::AuditRepositoryT,
+};
+
+#[derive(Default)]
+pub struct AuditQuery;
+
+#[Object]
+impl<'a> AuditQuery {
+    /// Журнал аудита, от новых записей к старым.
+    ///
+    /// Постраничный вывод вперед по курсору `after`,
+    /// не более `AUDIT_PAGE_MAX` записей на странице.
+    #[graphql(
+        guard = "AuthGuard::scope(Scope::Admin)",
+        complexity = "DB_COST + page_size(first, AUDIT_PAGE_SIZE, AUDIT_PAGE_MAX) * child_complexity"
+    )]
+    async fn audit_log(
+        &'a self,
+        ctx: &'a Context<'_>,
+        filter: Option<AuditFilter>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> GraphQLResult<Connection<String, AuditEntry>> {
+        let audit_service = ctx.data::<Arc<dyn AuditRepositoryT>>()?;
+        let filter = filter.unwrap_or_default();
+        let limit = page_size(first, AUDIT_PAGE_SIZE, AUDIT_PAGE_MAX);
+
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<String>, _, _, _| async move {
+                let cursor = match after {
+                    Some(after) => Some(
+                        AuditEntry::parse_cursor(&after)
+                            .ok_or_else(|| crate::unprocessable!("after", None))?,
+                    ),
+                    None => None,
+                };
+                let has_previous_page = cursor.is_some();
+
+                let mut entries = audit_service.find(&filter, cursor, limit + 1).await?;
+                let has_next_page = truncate_page(&mut entries, limit);
+
+                let mut connection = Connection::new(has_previous_page, has_next_page);
+                connection.edges.extend(
+                    entries
+                        .into_iter()
+                        .map(|entry| Edge::new(entry.cursor(), entry)),
+                );
+
+                Ok::<_, GraphQLError>(connection)
+            },
+        )
+        .await
+    }
+}
@@ -0,0 +1,7 @@
+pub mod audit_export;
+pub mod audit_model;
+pub mod audit_mutation;
+pub mod audit_repository;
+pub mod audit_resolver;
+
+mod audit_node;
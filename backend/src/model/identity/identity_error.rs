@@ -0,0 +1,10 @@
+lazy_static! {
+    pub static ref ERR_IDENTITY__STATE_MISMATCH: &'static str =
+        "Authorization state does not match";
+    pub static ref ERR_IDENTITY__FLOW_USED: &'static str =
+        "Authorization flow has already been completed";
+    pub static ref ERR_IDENTITY__EMAIL_NOT_VERIFIED: &'static str =
+        "Identity provider did not confirm the email address";
+    pub static ref ERR_IDENTITY__NO_PROFILE: &'static str =
+        "No profile is registered with this email address";
+}
@@ -0,0 +1,31 @@
+use async_graphql::SimpleObject;
+
+/// Срок действия токена входа через провайдера в минутах
+pub(crate) const OIDC_FLOW_MINUTES: i64 = 10;
+
+/// Параметры входа через провайдера OpenID Connect.
+///
+/// Клиент перенаправляет пользователя по `authorization_url`,
+/// а `flow_token` сохраняет у себя до возврата от провайдера.
+#[derive(SimpleObject)]
+pub struct OidcAuthorization {
+    authorization_url: String,
+    flow_token: String,
+}
+
+impl OidcAuthorization {
+    pub(super) fn new(authorization_url: String, flow_token: String) -> Self {
+        Self {
+            authorization_url,
+            flow_token,
+        }
+    }
+}
+
+/// Состояние входа, передаваемое в подписанном `flow_token`
+#[derive(Serialize, Deserialize)]
+pub(super) struct OidcFlow {
+    pub(super) state: String,
+    pub(super) nonce: String,
+    pub(super) code_verifier: String,
+}
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use neo4rs::Graph;
+use std::sync::Arc;
+
+use crate::app::db::cypher;
+use crate::{app::core::error::CustomError, neo4j_result};
+
+type EmptyResult<'a> = Result<(), CustomError<'a>>;
+
+/// Учетные записи внешних провайдеров, связанные с профилями.
+///
+/// Хранятся узлами :ExternalIdentity, связанными с :Profile.
+/// Учетная запись определяется парой из имени провайдера
+/// и утверждения `sub` его ID токена.
+#[async_trait]
+pub trait IdentityRepositoryT: Send + Sync {
+    async fn link(&self, profile_id: String, provider: String, subject: String) -> EmptyResult;
+
+    async fn find_profile_id(
+        &self,
+        provider: String,
+        subject: String,
+    ) -> Result<Option<String>, CustomError>;
+}
+
+pub struct IdentityRepository {
+    neo: Arc<Graph>,
+}
+
+impl IdentityRepository {
+    pub fn new(neo4j: &Arc<Graph>) -> Self {
+        Self { neo: neo4j.clone() }
+    }
+}
+
+#[async_trait]
+impl IdentityRepositoryT for IdentityRepository {
+    /* ======================== MUTATIONS ======================== */
+
+    async fn link(&self, profile_id: String, provider: String, subject: String) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile) WHERE p.id = $profile_id
+                MERGE (p)-[:HAS_IDENTITY]->(i:ExternalIdentity {provider: $provider, subject: $subject})
+                ON CREATE SET i.linked_at = $timestamp
+            ",
+        )
+        .param("profile_id", profile_id)
+        .param("provider", provider)
+        .param("subject", subject)
+        .param("timestamp", Utc::now().timestamp());
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /* ======================== QUERYS ======================== */
+
+    async fn find_profile_id(
+        &self,
+        provider: String,
+        subject: String,
+    ) -> Result<Option<String>, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_IDENTITY]->(i:ExternalIdentity)
+                WHERE i.provider = $provider AND i.subject = $subject
+                RETURN p.id AS id
+            ",
+        )
+        .param("provider", provider)
+        .param("subject", subject);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        if let Ok(Some(row)) = result.next().await {
+            return Ok(row.get::<String>("id"));
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,163 @@
+use async_graphql::{Context, Object, Result as GraphQLResult};
+use chrono::Duration;
+use std::sync::Arc;
+
+use crate::app::api::security::{
+    auth::{ActionClaims, Token, TokenPurpose},
+    oidc::{random_string, OidcClient, Pkce, OIDC_PROVIDERS},
+};
+use crate::app::core::error::{
+    CustomError,
+    CustomErrorKind::{NotFound, TokenInvalid},
+};
+use crate::model::profile::{
+    profile_mutation::LoginResult, profile_repository::ProfileRepositoryT,
+};
+use crate::model::token::token_model::complete_login;
+
+use super::{
+    identity_error::{
+        ERR_IDENTITY__EMAIL_NOT_VERIFIED, ERR_IDENTITY__FLOW_USED, ERR_IDENTITY__NO_PROFILE,
+        ERR_IDENTITY__STATE_MISMATCH,
+    },
+    identity_model::{OidcAuthorization, OidcFlow, OIDC_FLOW_MINUTES},
+    identity_repository::IdentityRepositoryT,
+};
+
+#[derive(Default)]
+pub struct IdentityMutation;
+
+#[Object]
+impl<'a> IdentityMutation {
+    /// Метод начала входа через провайдера OpenID Connect.
+    ///
+    /// Возвращает адрес страницы входа провайдера и подписанный
+    /// токен с параметрами PKCE, который клиент предъявляет в `oidcLogin`.
+    async fn oidc_authorize(
+        &'a self,
+        _ctx: &'a Context<'_>,
+        provider: String,
+    ) -> GraphQLResult<OidcAuthorization> {
+        let oidc_provider = match OIDC_PROVIDERS.get(&provider) {
+            Some(oidc_provider) => oidc_provider,
+            None => return Err(crate::not_found!("provider").into()),
+        };
+
+        let pkce = Pkce::new();
+        let flow = OidcFlow {
+            state: random_string(),
+            nonce: random_string(),
+            code_verifier: pkce.verifier().to_string(),
+        };
+
+        let authorization_url = oidc_provider
+            .authorization_url(&pkce, &flow.state, &flow.nonce)
+            .map_err(|err| crate::internal!(&err.to_string()))?;
+        let flow_token = Token::encode(ActionClaims::new(
+            provider,
+            TokenPurpose::OidcLogin,
+            Some(serde_json::to_string(&flow)?),
+            Duration::minutes(OIDC_FLOW_MINUTES),
+        ))
+        .map_err(|err| crate::internal!(&err.to_string()))?;
+
+        Ok(OidcAuthorization::new(authorization_url, flow_token))
+    }
+
+    /// Метод завершения входа через провайдера OpenID Connect.
+    ///
+    /// Профиль определяется по ранее связанной учетной записи провайдера,
+    /// иначе по адресу почты, подтвержденному провайдером, после чего
+    /// учетная запись связывается с профилем. Ответ совпадает с `login`.
+    ///
+    /// Токен входа принимается один раз.
+    async fn oidc_login(
+        &'a self,
+        ctx: &'a Context<'_>,
+        flow_token: String,
+        code: String,
+        state: String,
+    ) -> GraphQLResult<LoginResult> {
+        let claims = ActionClaims::decode(&flow_token, TokenPurpose::OidcLogin)?;
+        let flow: OidcFlow = serde_json::from_str(claims.data().unwrap_or_default())
+            .map_err(|_| CustomError::new().kind(TokenInvalid).build())?;
+
+        if flow.state != state {
+            return Err(CustomError::new()
+                .kind(TokenInvalid)
+                .details(&ERR_IDENTITY__STATE_MISMATCH)
+                .build()
+                .into());
+        }
+
+        let oidc_provider = match OIDC_PROVIDERS.get(claims.sub()) {
+            Some(oidc_provider) => oidc_provider,
+            None => return Err(crate::not_found!("provider").into()),
+        };
+
+        let oidc_client = ctx.data::<Arc<OidcClient>>()?;
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let identity_service = ctx.data::<Arc<dyn IdentityRepositoryT>>()?;
+
+        if !oidc_client.use_flow(&flow.nonce, claims.exp()) {
+            return Err(CustomError::new()
+                .kind(TokenInvalid)
+                .details(&ERR_IDENTITY__FLOW_USED)
+                .build()
+                .into());
+        }
+
+        let id_claims = oidc_client
+            .exchange_code(oidc_provider, &code, &flow.code_verifier, &flow.nonce)
+            .await?;
+
+        let linked = identity_service
+            .find_profile_id(claims.sub().to_string(), id_claims.sub().to_string())
+            .await?;
+
+        let profile = match linked {
+            Some(profile_id) => profile_service.get_data(profile_id).await?,
+            None => {
+                let email = match id_claims.verified_email() {
+                    Some(email) => email,
+                    None => {
+                        return Err(crate::unprocessable!(
+                            "email",
+                            Some(ERR_IDENTITY__EMAIL_NOT_VERIFIED.to_string())
+                        )
+                        .into())
+                    }
+                };
+
+                let profile = match profile_service.get_by_email(email.to_string()).await {
+                    Ok(profile) => profile,
+                    Err(err) if !matches!(err.kind(), NotFound(_)) => return Err(err.into()),
+                    Err(_) => {
+                        return Err(CustomError::new()
+                            .kind(NotFound("profile"))
+                            .details(&ERR_IDENTITY__NO_PROFILE)
+                            .build()
+                            .into())
+                    }
+                };
+
+                identity_service
+                    .link(
+                        profile.id.to_string(),
+                        claims.sub().to_string(),
+                        id_claims.sub().to_string(),
+                    )
+                    .await?;
+
+                // Провайдер подтвердил владение адресом
+                if !profile.email_verified {
+                    profile_service.verify_email(profile.id.to_string()).await?;
+                }
+
+                profile
+            }
+        };
+
+        complete_login(ctx, &profile).await
+    }
+}
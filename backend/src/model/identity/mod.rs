@@ -0,0 +1,4 @@
+pub mod identity_error;
+pub mod identity_model;
+pub mod identity_repository;
+pub mod identity_resolver;
@@ -60,6 +60,36 @@ pub enum CefrKind {
     C2,
 }
 
+impl CefrKind {
+    pub const ALL: [CefrKind; 9] = [
+        CefrKind::A1,
+        CefrKind::A,
+        CefrKind::A2,
+        CefrKind::B1,
+        CefrKind::B,
+        CefrKind::B2,
+        CefrKind::C1,
+        CefrKind::C,
+        CefrKind::C2,
+    ];
+
+    /// Порядок уровня по шкале CEFR.
+    /// Общий уровень, например `B`, находится между `B1` и `B2`.
+    pub fn rank(&self) -> u8 {
+        match self {
+            CefrKind::A1 => 1,
+            CefrKind::A => 2,
+            CefrKind::A2 => 3,
+            CefrKind::B1 => 4,
+            CefrKind::B => 5,
+            CefrKind::B2 => 6,
+            CefrKind::C1 => 7,
+            CefrKind::C => 8,
+            CefrKind::C2 => 9,
+        }
+    }
+}
+
 #[derive(
     Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Enum, Display, EnumString,
 )]
@@ -1,3 +1,12 @@
 pub mod profile;
 pub mod language;
+pub mod token;
+pub mod session;
+pub mod admin;
+pub mod two_factor;
+pub mod identity;
+pub mod api_key;
+pub mod audit;
+pub mod partner;
+pub mod suggestion;
 // pub mod chat;
\ No newline at end of file
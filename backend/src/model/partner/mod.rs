@@ -0,0 +1,5 @@
+pub mod partner_model;
+pub mod partner_repository;
+pub mod partner_resolver;
+
+mod partner_node;
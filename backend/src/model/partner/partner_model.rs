@@ -0,0 +1,118 @@
+use async_graphql::{connection::CursorType, SimpleObject};
+
+use crate::model::language::language_model::Studied;
+use crate::model::profile::profile_model::Profile;
+
+/// Количество партнеров на странице по умолчанию
+pub(super) const PARTNERS_PAGE_SIZE: usize = 20;
+/// Максимальное количество партнеров на странице
+pub(super) const PARTNERS_PAGE_MAX: usize = 50;
+/// Партнер, заходивший не позднее этого срока в секундах, считается активным
+pub(super) const ACTIVE_PERIOD: i64 = 7 * 24 * 60 * 60;
+/// Близость уровней владения, начиная с которой они считаются близкими
+pub(super) const CLOSE_LEVEL_FIT: f64 = 0.8;
+
+/// Предложенный партнер для языкового обмена
+#[derive(SimpleObject)]
+pub struct PartnerMatch {
+    pub(super) profile: Profile,
+    /// Оценка совпадения от 0 до 1
+    pub(super) score: f64,
+    pub(super) explanation: MatchExplanation,
+    /// Время, на которое рассчитана оценка активности
+    #[graphql(skip)]
+    pub(super) ranked_at: i64,
+}
+
+impl PartnerMatch {
+    /// Курсор совпадения для постраничного вывода
+    pub(super) fn cursor(&self) -> PartnerCursor {
+        PartnerCursor {
+            ranked_at: self.ranked_at,
+            score: self.score,
+            id: self.profile.id.to_string(),
+        }
+    }
+}
+
+/// Курсор списка партнеров.
+///
+/// Партнеры упорядочены по убыванию оценки, с одинаковой
+/// оценкой - по идентификатору. Оценка активности зависит от
+/// текущего времени, поэтому курсор хранит время первой страницы,
+/// и следующие страницы рассчитываются на него же.
+pub struct PartnerCursor {
+    pub(super) ranked_at: i64,
+    pub(super) score: f64,
+    pub(super) id: String,
+}
+
+impl CursorType for PartnerCursor {
+    type Error = &'static str;
+
+    fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+        let mut parts = s.splitn(3, ':');
+        let mut next = || parts.next().ok_or("Invalid partner cursor");
+
+        Ok(Self {
+            ranked_at: next()?.parse().map_err(|_| "Invalid partner cursor")?,
+            score: next()?.parse().map_err(|_| "Invalid partner cursor")?,
+            id: next()?.to_string(),
+        })
+    }
+
+    fn encode_cursor(&self) -> String {
+        format!("{}:{}:{}", self.ranked_at, self.score, self.id)
+    }
+}
+
+/// Почему партнер был предложен
+#[derive(SimpleObject)]
+pub struct MatchExplanation {
+    /// Изучаемые мной языки, родные для партнера, с моим уровнем
+    pub(super) teaches: Vec<Studied>,
+    /// Мои родные языки, которые изучает партнер, с его уровнем
+    pub(super) learns: Vec<Studied>,
+    /// Близость уровней владения от 0 до 1
+    pub(super) level_fit: f64,
+    /// Количество профилей, на которые подписаны оба
+    pub(super) shared_follows: i64,
+    /// Время последней активности партнера
+    pub(super) last_active_at: i64,
+    /// Причины в читаемом виде
+    pub(super) reasons: Vec<String>,
+}
+
+impl MatchExplanation {
+    pub(super) fn describe(&mut self, now: i64) {
+        let mut reasons = Vec::new();
+
+        for studied in &self.teaches {
+            reasons.push(format!(
+                "Native {} speaker, you study it at {}",
+                studied.lang, studied.cefr
+            ));
+        }
+
+        for studied in &self.learns {
+            reasons.push(format!(
+                "Studies {} at {}, your native language",
+                studied.lang, studied.cefr
+            ));
+        }
+
+        if self.level_fit >= CLOSE_LEVEL_FIT {
+            reasons.push("Your language levels are close".to_string());
+        }
+
+        if self.shared_follows > 0 {
+            reasons.push(format!("You both follow {} profiles", self.shared_follows));
+        }
+
+        if now - self.last_active_at <= ACTIVE_PERIOD {
+            reasons.push("Active in the last week".to_string());
+        }
+
+        self.reasons = reasons;
+    }
+}
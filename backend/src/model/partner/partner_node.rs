@@ -0,0 +1,57 @@
+use anyhow::Result;
+use neo4rs::Row;
+use std::str::FromStr;
+
+use crate::app::core::error::CustomError;
+use crate::model::language::language_model::{CefrKind, Language, Studied};
+use crate::model::profile::profile_model::Profile;
+
+use super::partner_model::{MatchExplanation, PartnerMatch};
+
+/// Разделитель языков в списке
+const LANGS_SEPARATOR: char = ';';
+/// Разделитель языка и уровня владения
+const LEVEL_SEPARATOR: char = ':';
+
+impl<'a> PartnerMatch {
+    pub(super) fn parse_row(row: &Row, now: i64) -> Result<PartnerMatch, CustomError<'a>> {
+        let pnode = row
+            .get::<neo4rs::Node>("p")
+            .ok_or_else(|| crate::internal!("Partner node is missing"))?;
+
+        let mut explanation = MatchExplanation {
+            teaches: parse_studied(&row.get::<String>("teaches").unwrap_or_default())?,
+            learns: parse_studied(&row.get::<String>("learns").unwrap_or_default())?,
+            level_fit: row.get::<f64>("level_fit").unwrap_or(0.0),
+            shared_follows: row.get::<i64>("shared").unwrap_or(0),
+            last_active_at: row.get::<i64>("last_active").unwrap_or(0),
+            reasons: Vec::new(),
+        };
+        explanation.describe(now);
+
+        Ok(PartnerMatch {
+            profile: Profile::parse_query_resp(pnode)?,
+            score: row.get::<f64>("score").unwrap_or(0.0),
+            explanation,
+            ranked_at: now,
+        })
+    }
+}
+
+/// Разбор списка вида `English:B1;German:A2;`
+fn parse_studied<'a>(value: &str) -> Result<Vec<Studied>, CustomError<'a>> {
+    value
+        .split(LANGS_SEPARATOR)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            let (lang, cefr) = item
+                .split_once(LEVEL_SEPARATOR)
+                .ok_or_else(|| crate::internal!("Invalid language level"))?;
+
+            Ok(Studied::new(
+                CefrKind::from_str(cefr)?,
+                Language::from_str(lang)?,
+            ))
+        })
+        .collect()
+}
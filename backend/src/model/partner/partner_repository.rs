@@ -0,0 +1,125 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use neo4rs::Graph;
+use std::sync::Arc;
+
+use crate::app::db::cypher;
+use crate::{app::core::error::CustomError, neo4j_result};
+
+use super::partner_model::{PartnerCursor, PartnerMatch};
+
+#[async_trait]
+pub trait PartnerRepositoryT: Send + Sync {
+    async fn find(
+        &self,
+        profile_id: String,
+        after: Option<PartnerCursor>,
+        limit: usize,
+    ) -> Result<Vec<PartnerMatch>, CustomError>;
+}
+
+pub struct PartnerRepository {
+    neo: Arc<Graph>,
+}
+
+impl PartnerRepository {
+    pub fn new(neo4j: &Arc<Graph>) -> Self {
+        Self { neo: neo4j.clone() }
+    }
+}
+
+#[async_trait]
+impl PartnerRepositoryT for PartnerRepository {
+    /* ======================== QUERYS ======================== */
+
+    /// Поиск партнеров для языкового обмена.
+    ///
+    /// Партнер знает как родной хотя бы один изучаемый мной язык
+    /// и изучает хотя бы один мой родной язык. Оценка складывается из:
+    /// 1. взаимного покрытия языков (0.4) - доли моих изучаемых языков,
+    ///    родных для партнера, и доли его изучаемых языков, родных для меня;
+    /// 2. близости уровней CEFR (0.25) - разницы среднего уровня
+    ///    в языках, которым партнер может научить, и в языках, которым могу научить я;
+    /// 3. общих подписок (0.15);
+    /// 4. недавней активности (0.2) - по последнему использованию сессии.
+    ///
+    /// Страница выбирается после курсора `after` по оценке и идентификатору,
+    /// активность считается на время курсора. Оценка передается строкой,
+    /// так как драйвер не поддерживает дробные числа в параметрах.
+    async fn find(
+        &self,
+        profile_id: String,
+        after: Option<PartnerCursor>,
+        limit: usize,
+    ) -> Result<Vec<PartnerMatch>, CustomError> {
+        let now = after
+            .as_ref()
+            .map_or_else(|| Utc::now().timestamp(), |after| after.ranked_at);
+        let (after_score, after_id) = after
+            .map(|after| (after.score.to_string(), after.id))
+            .unwrap_or_default();
+
+        let query = cypher::query(
+            "
+                WITH {A1: 1.0, A: 1.5, A2: 2.0, B1: 3.0, B: 3.5, B2: 4.0, C1: 5.0, C: 5.5, C2: 6.0}
+                    AS levels
+                MATCH (me:Profile) WHERE me.id = $id
+                MATCH (me)-[:NATIVE_SPEAKER]->(:Language)<-[:STUDIED]-(p:Profile),
+                    (p)-[:NATIVE_SPEAKER]->(:Language)<-[:STUDIED]-(me)
+                WHERE p <> me AND NOT p:Suspended AND NOT p:Unverified AND NOT p:PendingDeletion
+                WITH DISTINCT levels, me, p
+
+                WITH levels, me, p,
+                    [(me)-[s:STUDIED]->(l:Language)<-[:NATIVE_SPEAKER]-(p) | {code: l.code, cefr: s.cefr}]
+                        AS teaches,
+                    [(me)-[:NATIVE_SPEAKER]->(l:Language)<-[s:STUDIED]-(p) | {code: l.code, cefr: s.cefr}]
+                        AS learns,
+                    size([(me)-[:STUDIED]->(l:Language) | l]) AS my_studied,
+                    size([(p)-[:STUDIED]->(l:Language) | l]) AS their_studied,
+                    size([(me)-[:SUBSCRIBE]->(f:Profile)<-[:SUBSCRIBE]-(p) | f]) AS shared,
+                    reduce(
+                        last = p.updated_at,
+                        used IN [(p)-[:HAS_SESSION]->(s:Session) | s.last_used_at] |
+                        CASE WHEN used > last THEN used ELSE last END
+                    ) AS last_active
+
+                WITH p, teaches, learns, shared, last_active,
+                    (toFloat(size(teaches)) / my_studied + toFloat(size(learns)) / their_studied) / 2
+                        AS coverage,
+                    1 - abs(
+                        reduce(sum = 0.0, x IN teaches | sum + levels[x.cefr]) / size(teaches)
+                        - reduce(sum = 0.0, x IN learns | sum + levels[x.cefr]) / size(learns)
+                    ) / 5 AS level_fit,
+                    1 - 1.0 / (1 + shared) AS social,
+                    1.0 / (1 + ($now - last_active) / 604800.0) AS activity
+
+                WITH p, teaches, learns, shared, last_active, level_fit,
+                    0.4 * coverage + 0.25 * level_fit + 0.15 * social + 0.2 * activity AS score
+                WHERE $after_id = ''
+                    OR score < toFloat($after_score)
+                    OR (score = toFloat($after_score) AND p.id > $after_id)
+
+                RETURN p, shared, last_active, level_fit, score,
+                    reduce(s = '', x IN teaches | s + x.code + ':' + x.cefr + ';') AS teaches,
+                    reduce(s = '', x IN learns | s + x.code + ':' + x.cefr + ';') AS learns
+                ORDER BY score DESC, p.id
+                LIMIT $limit
+            ",
+        )
+        .param("id", profile_id)
+        .param("now", now)
+        .param("after_score", after_score)
+        .param("after_id", after_id)
+        .param("limit", limit as i64);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+        let mut output: Vec<PartnerMatch> = Vec::new();
+
+        while let Ok(Some(row)) = result.next().await {
+            output.push(PartnerMatch::parse_row(&row, now)?);
+        }
+
+        Ok(output)
+    }
+}
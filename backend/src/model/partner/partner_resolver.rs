@@ -0,0 +1,63 @@
+use async_graphql::connection::{query, Connection, Edge};
+use async_graphql::{Context, Error as GraphQLError, Object, Result as GraphQLResult};
+use std::sync::Arc;
+
+use crate::app::api::graphql::limits::{page_size, truncate_page, DB_COST};
+use crate::app::api::security::auth::{get_access_claims, AuthGuard};
+use crate::app::api::security::scope::Scope;
+
+use super::{
+    partner_model::{PartnerCursor, PartnerMatch, PARTNERS_PAGE_MAX, PARTNERS_PAGE_SIZE},
+    partner_repository::PartnerRepositoryT,
+};
+
+#[derive(Default)]
+pub struct PartnerQuery;
+
+#[Object]
+impl<'a> PartnerQuery {
+    /// Подбор партнеров для языкового обмена.
+    ///
+    /// Возвращает профили, для которых родные языки покрывают
+    /// изучаемые мной, а изучаемые - мои родные, от лучшего
+    /// совпадения к худшему. Каждое совпадение содержит объяснение.
+    ///
+    /// Постраничный вывод вперед по курсору `after`.
+    #[graphql(
+        guard = "AuthGuard::scope(Scope::ProfileRead)",
+        complexity = "DB_COST + page_size(first, PARTNERS_PAGE_SIZE, PARTNERS_PAGE_MAX) * child_complexity"
+    )]
+    async fn find_partners(
+        &'a self,
+        ctx: &'a Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> GraphQLResult<Connection<PartnerCursor, PartnerMatch>> {
+        let partner_service = ctx.data::<Arc<dyn PartnerRepositoryT>>()?;
+        let profile_id = get_access_claims(ctx).sub().to_string();
+        let limit = page_size(first, PARTNERS_PAGE_SIZE, PARTNERS_PAGE_MAX);
+
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<PartnerCursor>, _, _, _| async move {
+                let has_previous_page = after.is_some();
+
+                let mut partners = partner_service.find(profile_id, after, limit + 1).await?;
+                let has_next_page = truncate_page(&mut partners, limit);
+
+                let mut connection = Connection::new(has_previous_page, has_next_page);
+                connection.edges.extend(
+                    partners
+                        .into_iter()
+                        .map(|partner| Edge::new(partner.cursor(), partner)),
+                );
+
+                Ok::<_, GraphQLError>(connection)
+            },
+        )
+        .await
+    }
+}
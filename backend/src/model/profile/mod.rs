@@ -2,7 +2,9 @@ pub mod profile_model;
 pub mod profile_resolver;
 pub mod profile_repository;
 pub mod profile_error;
+pub mod profile_mutation;
+pub mod profile_privacy;
+pub mod profile_follows;
 
-mod profile_mutation;
 mod profile_connections;
 mod profile_node;
\ No newline at end of file
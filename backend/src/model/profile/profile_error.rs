@@ -1,3 +1,14 @@
 lazy_static! {
     pub static ref ERR_PROF__SELF_SUBSCRIBE: &'static str = "You can't subscribe yourself";
+    pub static ref ERR_PROF__EMAIL_NOT_VERIFIED: &'static str = "Email address is not verified";
+    pub static ref ERR_PROF__EMAIL_ALREADY_VERIFIED: &'static str =
+        "Email address is already verified";
+    pub static ref ERR_PROF__CHALLENGE_UNSOLVED: &'static str =
+        "Registration challenge is not solved";
+    pub static ref ERR_PROF__CHALLENGE_USED: &'static str =
+        "Registration challenge has already been used";
+    pub static ref ERR_PROF__CHALLENGE_OUTDATED: &'static str =
+        "Registration challenge is too easy, request a new one";
+    pub static ref ERR_PROF__DELETION_BY_ADMIN: &'static str =
+        "Profile is scheduled for deletion by an administrator";
 }
\ No newline at end of file
@@ -0,0 +1,169 @@
+use async_graphql::connection::{
+    query, Connection, ConnectionNameType, CursorType, Edge, EdgeNameType, EmptyFields,
+};
+use async_graphql::{
+    Context, Error as GraphQLError, OutputType, Result as GraphQLResult, SimpleObject,
+};
+use std::sync::Arc;
+
+use crate::app::api::graphql::limits::{page_size, truncate_page};
+
+use super::profile_model::Profile;
+use super::profile_repository::ProfileRepositoryT;
+
+/// Количество профилей на странице подписок по умолчанию
+pub(super) const FOLLOWS_PAGE_SIZE: usize = 20;
+/// Максимальное количество профилей на странице подписок
+pub(super) const FOLLOWS_PAGE_MAX: usize = 50;
+
+/// Список связей :SUBSCRIBE профиля
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FollowKind {
+    /// Подписанные на профиль
+    Followers,
+    /// Профили, на которые подписан профиль
+    Following,
+    /// Взаимные подписки
+    Mutual,
+}
+
+/// Курсор списка подписок.
+///
+/// Списки упорядочены от новых подписок к старым, профили
+/// с одинаковым временем подписки - по идентификатору.
+pub struct FollowCursor {
+    pub(super) followed_at: i64,
+    pub(super) id: String,
+}
+
+impl CursorType for FollowCursor {
+    type Error = &'static str;
+
+    fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+        let (followed_at, id) = s.split_once(':').ok_or("Invalid follow cursor")?;
+
+        Ok(Self {
+            followed_at: followed_at.parse().map_err(|_| "Invalid follow cursor")?,
+            id: id.to_string(),
+        })
+    }
+
+    fn encode_cursor(&self) -> String {
+        format!("{}:{}", self.followed_at, self.id)
+    }
+}
+
+/// Поля ребра списка подписок
+#[derive(SimpleObject)]
+pub struct FollowEdgeFields {
+    /// Время подписки, для взаимных подписок - время ответной подписки
+    followed_at: i64,
+}
+
+/// Имя типа списка подписок в схеме, `ProfileConnection`
+/// уже занято результатом поиска профилей
+pub struct FollowConnectionName;
+
+impl ConnectionNameType for FollowConnectionName {
+    fn type_name<T: OutputType>() -> String {
+        "FollowConnection".to_string()
+    }
+}
+
+pub struct FollowEdgeName;
+
+impl EdgeNameType for FollowEdgeName {
+    fn type_name<T: OutputType>() -> String {
+        "FollowEdge".to_string()
+    }
+}
+
+pub type FollowConnection = Connection<
+    FollowCursor,
+    Profile,
+    EmptyFields,
+    FollowEdgeFields,
+    FollowConnectionName,
+    FollowEdgeName,
+>;
+
+impl Profile {
+    /// Страница списка подписок профиля после курсора `after`
+    pub(super) async fn follows(
+        &self,
+        ctx: &Context<'_>,
+        kind: FollowKind,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> GraphQLResult<FollowConnection> {
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let profile_id = self.id.to_string();
+        let limit = page_size(first, FOLLOWS_PAGE_SIZE, FOLLOWS_PAGE_MAX);
+
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<FollowCursor>, _, _, _| async move {
+                let has_previous_page = after.is_some();
+
+                let mut follows = profile_service
+                    .get_follows(profile_id, kind, after, limit + 1)
+                    .await?;
+                let has_next_page = truncate_page(&mut follows, limit);
+
+                let mut connection = FollowConnection::new(has_previous_page, has_next_page);
+                connection
+                    .edges
+                    .extend(follows.into_iter().map(|(profile, followed_at)| {
+                        Edge::with_additional_fields(
+                            FollowCursor {
+                                followed_at,
+                                id: profile.id.to_string(),
+                            },
+                            profile,
+                            FollowEdgeFields { followed_at },
+                        )
+                    }));
+
+                Ok::<_, GraphQLError>(connection)
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_encoded_cursor() {
+        let cursor = FollowCursor {
+            followed_at: 1660000000,
+            id: "5f3a7c1e-2b4d-4e8f-9a6b-0c1d2e3f4a5b".to_string(),
+        };
+
+        let decoded = FollowCursor::decode_cursor(&cursor.encode_cursor()).unwrap();
+
+        assert_eq!(decoded.followed_at, cursor.followed_at);
+        assert_eq!(decoded.id, cursor.id);
+    }
+
+    #[test]
+    fn keeps_separator_in_id() {
+        let decoded = FollowCursor::decode_cursor("1660000000:a:b").unwrap();
+
+        assert_eq!(decoded.followed_at, 1660000000);
+        assert_eq!(decoded.id, "a:b");
+    }
+
+    #[test]
+    fn rejects_invalid_cursor() {
+        assert!(FollowCursor::decode_cursor("").is_err());
+        assert!(FollowCursor::decode_cursor("1660000000").is_err());
+        assert!(FollowCursor::decode_cursor("yesterday:id").is_err());
+        assert!(FollowCursor::decode_cursor(":id").is_err());
+    }
+}
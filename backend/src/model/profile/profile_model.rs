@@ -1,16 +1,31 @@
 use anyhow::Result;
 use argon2::Config;
-use async_graphql::{Enum, Object};
+use async_graphql::{Context, Enum, Object, Result as GraphQLResult, SimpleObject};
 use chrono::Utc;
-use neo4j_cypher::CypQue;
 use rand::Rng;
 use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Arc;
 use strum_macros::{Display, EnumString};
+use tokio::sync::OnceCell;
 use uuid::Uuid;
 
+use crate::app::api::graphql::limits::{page_size, DB_COST};
 use crate::app::api::security::auth::AuthGuard;
+use crate::app::core::error::{CustomError, CustomErrorKind::AccountSuspended};
 
+use super::profile_follows::{FollowConnection, FollowKind, FOLLOWS_PAGE_MAX, FOLLOWS_PAGE_SIZE};
 use super::profile_mutation::ProfileRegistrationInput;
+use super::profile_privacy::PrivacySettings;
+use super::profile_repository::ProfileRepositoryT;
+
+lazy_static! {
+    /// Срок в днях между запросом удаления профиля и окончательным
+    /// удалением. Вход в течение этого срока отменяет удаление.
+    pub static ref DELETION_GRACE_DAYS: i64 = dotenv!("ACCOUNT_DELETION_GRACE_DAYS")
+        .parse()
+        .expect("ACCOUNT_DELETION_GRACE_DAYS must be a number of days");
+}
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Enum, Display, EnumString)]
 pub enum Permission {
@@ -27,20 +42,84 @@ pub enum Permission {
     Admin,
 }
 
+impl Permission {
+    /// Роль профиля хранится меткой узла :Profile.
+    /// Профиль без метки роли считается пользователем.
+    pub fn from_labels(labels: &[String]) -> Self {
+        labels
+            .iter()
+            .find_map(|label| Permission::from_str(label).ok())
+            .unwrap_or(Permission::User)
+    }
+
+    /// Уровень роли в иерархии Admin ⊇ Developer ⊇ User ⊇ Guest
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Guest => 0,
+            Self::User => 1,
+            Self::Developer => 2,
+            Self::Admin => 3,
+        }
+    }
+
+    /// Проверка, что роль включает в себя указанную
+    pub fn includes(&self, other: Permission) -> bool {
+        self.rank() >= other.rank()
+    }
+}
+
 impl Default for Permission {
     fn default() -> Self {
         Self::Guest
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, CypQue)]
+/// Блокировка профиля администратором.
+/// У бессрочной блокировки срок `until` не указан.
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Suspension {
+    pub(crate) reason: String,
+    pub(crate) until: Option<i64>,
+}
+
+impl Suspension {
+    pub(crate) fn is_active(&self) -> bool {
+        self.until
+            .map_or(true, |until| until > Utc::now().timestamp())
+    }
+
+    pub(crate) fn error<'a>(&self) -> CustomError<'a> {
+        let details = match self.until {
+            Some(until) => format!("{} (until {})", self.reason, until),
+            None => self.reason.clone(),
+        };
+
+        CustomError::new()
+            .kind(AccountSuspended)
+            .details(&details)
+            .build()
+    }
+}
+
+/// Количество профилей на странице поиска по умолчанию
+pub(super) const SEARCH_PAGE_SIZE: usize = 20;
+/// Максимальное количество профилей на странице поиска
+pub(super) const SEARCH_PAGE_MAX: usize = 50;
+
+/// Дополнительные поля результата поиска профилей
+#[derive(SimpleObject)]
+pub struct SearchTotal {
+    /// Количество профилей, подходящих под фильтры
+    pub(super) total_count: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Profile {
-    pub(super) id: Uuid,
-    pub(super) email: String,
+    pub(crate) id: Uuid,
+    pub(crate) email: String,
     pub(super) hash: String,
-    #[cypher(skip)]
-    pub(super) permission: Permission,
-    pub(super) username: String,
+    pub(crate) permission: Permission,
+    pub(crate) username: String,
     pub(super) first_name: String,
     pub(super) last_name: Option<String>,
     pub(super) sex: u8,
@@ -48,6 +127,19 @@ pub struct Profile {
     pub(super) description: Option<String>,
     pub(super) created_at: i64,
     pub(super) updated_at: i64,
+    /// Хранится меткой :Unverified у профилей с неподтвержденной почтой
+    pub(crate) email_verified: bool,
+    pub(crate) privacy: PrivacySettings,
+    /// Хранится меткой :Suspended, истекшая блокировка не загружается
+    pub(crate) suspension: Option<Suspension>,
+    /// Время окончательного удаления, хранится вместе с меткой :PendingDeletion
+    pub(crate) deletion_scheduled_at: Option<i64>,
+    /// Профиль, запросивший удаление: сам профиль или администратор
+    pub(crate) deletion_requested_by: Option<String>,
+    /// Подписка запрашивающего на профиль, проверяется не больше
+    /// одного раза для всех полей с видимостью `Followers`
+    #[serde(skip)]
+    pub(super) followed_by_viewer: OnceCell<bool>,
 }
 
 impl Profile {
@@ -65,94 +157,214 @@ impl Profile {
             description: profile_input.description,
             created_at: Utc::now().timestamp(),
             updated_at: Utc::now().timestamp(),
+            email_verified: false,
+            privacy: PrivacySettings::default(),
+            suspension: None,
+            deletion_scheduled_at: None,
+            deletion_requested_by: None,
+            followed_by_viewer: OnceCell::new(),
         };
 
         Ok(profile.password_hashing()?)
     }
 
     fn password_hashing(mut self) -> Result<Self> {
+        self.hash = Self::hash_password(&self.hash)?;
+        Ok(self)
+    }
+
+    /// Получение argon2 хеша пароля со случайной солью
+    pub(crate) fn hash_password(password: &str) -> Result<String> {
         let salt: [u8; 32] = rand::thread_rng().gen();
         let config = Config::default();
 
-        self.hash = argon2::hash_encoded(self.hash.as_bytes(), &salt, &config)?;
-        Ok(self)
+        Ok(argon2::hash_encoded(password.as_bytes(), &salt, &config)?)
+    }
+
+    /// Отпечаток текущего хеша пароля.
+    ///
+    /// Токен сброса пароля привязывается к отпечатку, поэтому после
+    /// любой смены пароля ранее выданные токены становятся недействительны.
+    pub(crate) fn password_fingerprint(&self) -> String {
+        let digest = ring::digest::digest(&ring::digest::SHA256, self.hash.as_bytes());
+        base64::encode_config(digest.as_ref(), base64::URL_SAFE_NO_PAD)
+    }
+
+    /// Проверка пароля по сохраненному argon2 хешу
+    pub(crate) fn verify_password(&self, password: &str) -> Result<bool> {
+        Ok(argon2::verify_encoded(&self.hash, password.as_bytes())?)
     }
 }
 
+/// Поля `email`, `emailVerified`, `privacy`, `suspension` и `deletionScheduledAt`
+/// доступны только владельцу профиля и администраторам, для остальных
+/// они возвращаются как `null`. Видимость `lastName`, `sex`, `age` и
+/// `description` определяется настройками приватности, скрытое поле
+/// возвращается как `null`.
 #[Object]
 impl<'a> Profile {
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
+    #[graphql(guard = "AuthGuard::at_least(Permission::User)")]
     async fn id(&'a self) -> String {
         self.id.to_string()
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
-    async fn email(&'a self) -> &str {
-        &self.email
+    #[graphql(guard = "AuthGuard::at_least(Permission::User)")]
+    async fn email(&'a self, ctx: &'a Context<'_>) -> Option<&str> {
+        self.owner_only(ctx, self.email.as_str())
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))")]
+    #[graphql(guard = "AuthGuard::at_least(Permission::Developer)")]
     async fn permission(&'a self) -> Permission {
         self.permission
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
+    #[graphql(guard = "AuthGuard::at_least(Permission::User)")]
     async fn username(&'a self) -> &str {
         &self.username
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
+    #[graphql(guard = "AuthGuard::at_least(Permission::User)")]
     async fn first_name(&'a self) -> &str {
         &self.first_name
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
-    async fn last_name(&'a self) -> &Option<String> {
-        &self.last_name
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::User)",
+        complexity = "DB_COST"
+    )]
+    async fn last_name(&'a self, ctx: &'a Context<'_>) -> GraphQLResult<Option<&str>> {
+        match self.visible(ctx, self.privacy.last_name).await? {
+            true => Ok(self.last_name.as_deref()),
+            false => Ok(None),
+        }
+    }
+
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::User)",
+        complexity = "DB_COST"
+    )]
+    async fn sex(&'a self, ctx: &'a Context<'_>) -> GraphQLResult<Option<u8>> {
+        match self.visible(ctx, self.privacy.sex).await? {
+            true => Ok(Some(self.sex)),
+            false => Ok(None),
+        }
+    }
+
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::User)",
+        complexity = "DB_COST"
+    )]
+    async fn age(&'a self, ctx: &'a Context<'_>) -> GraphQLResult<Option<u8>> {
+        match self.visible(ctx, self.privacy.age).await? {
+            true => Ok(Some(self.age)),
+            false => Ok(None),
+        }
+    }
+
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::User)",
+        complexity = "DB_COST"
+    )]
+    async fn description(&'a self, ctx: &'a Context<'_>) -> GraphQLResult<Option<&str>> {
+        match self.visible(ctx, self.privacy.description).await? {
+            true => Ok(self.description.as_deref()),
+            false => Ok(None),
+        }
+    }
+
+    #[graphql(guard = "AuthGuard::at_least(Permission::User)")]
+    async fn email_verified(&'a self, ctx: &'a Context<'_>) -> Option<bool> {
+        self.owner_only(ctx, self.email_verified)
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
-    async fn sex(&'a self) -> u8 {
-        self.sex
+    #[graphql(guard = "AuthGuard::at_least(Permission::User)")]
+    async fn privacy(&'a self, ctx: &'a Context<'_>) -> Option<PrivacySettings> {
+        self.owner_only(ctx, self.privacy)
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
-    async fn age(&'a self) -> u8 {
-        self.age
+    #[graphql(guard = "AuthGuard::at_least(Permission::User)")]
+    async fn suspension(&'a self, ctx: &'a Context<'_>) -> Option<&Suspension> {
+        self.owner_only(ctx, self.suspension.as_ref()).flatten()
+    }
+
+    /// Подписанные на профиль, от новых подписок к старым
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::User)",
+        complexity = "DB_COST + page_size(first, FOLLOWS_PAGE_SIZE, FOLLOWS_PAGE_MAX) * child_complexity"
+    )]
+    async fn followers(
+        &'a self,
+        ctx: &'a Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> GraphQLResult<FollowConnection> {
+        self.follows(ctx, FollowKind::Followers, after, first).await
+    }
+
+    /// Профили, на которые подписан профиль, от новых подписок к старым
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::User)",
+        complexity = "DB_COST + page_size(first, FOLLOWS_PAGE_SIZE, FOLLOWS_PAGE_MAX) * child_complexity"
+    )]
+    async fn following(
+        &'a self,
+        ctx: &'a Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> GraphQLResult<FollowConnection> {
+        self.follows(ctx, FollowKind::Following, after, first).await
+    }
+
+    /// Профили, подписанные на профиль взаимно
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::User)",
+        complexity = "DB_COST + page_size(first, FOLLOWS_PAGE_SIZE, FOLLOWS_PAGE_MAX) * child_complexity"
+    )]
+    async fn mutual_follows(
+        &'a self,
+        ctx: &'a Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> GraphQLResult<FollowConnection> {
+        self.follows(ctx, FollowKind::Mutual, after, first).await
+    }
+
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::User)",
+        complexity = "DB_COST"
+    )]
+    async fn follower_count(&'a self, ctx: &'a Context<'_>) -> GraphQLResult<i64> {
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+
+        Ok(profile_service
+            .count_follows(self.id.to_string(), FollowKind::Followers)
+            .await?)
+    }
+
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::User)",
+        complexity = "DB_COST"
+    )]
+    async fn following_count(&'a self, ctx: &'a Context<'_>) -> GraphQLResult<i64> {
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+
+        Ok(profile_service
+            .count_follows(self.id.to_string(), FollowKind::Following)
+            .await?)
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
-    async fn description(&'a self) -> &Option<String> {
-        &self.description
+    /// Время окончательного удаления профиля, если удаление запрошено
+    #[graphql(guard = "AuthGuard::at_least(Permission::User)")]
+    async fn deletion_scheduled_at(&'a self, ctx: &'a Context<'_>) -> Option<i64> {
+        self.owner_only(ctx, self.deletion_scheduled_at).flatten()
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
+    #[graphql(guard = "AuthGuard::at_least(Permission::User)")]
     async fn created_at(&'a self) -> i64 {
         self.created_at
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
+    #[graphql(guard = "AuthGuard::at_least(Permission::User)")]
     async fn updated_at(&'a self) -> i64 {
         self.updated_at
     }
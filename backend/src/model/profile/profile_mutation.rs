@@ -1,7 +1,12 @@
-use async_graphql::{InputObject, Object};
+use async_graphql::{InputObject, Object, Union};
 use validator::Validate;
 
+use crate::app::api::security::password::validate_password;
 use crate::app::utils::{regex::RE_NAME, validation::validate_query};
+use crate::model::language::language_model::{CefrKind, Language};
+use crate::model::two_factor::two_factor_model::TwoFactorChallenge;
+
+use super::profile_privacy::{PrivacySettings, Visibility};
 
 #[derive(Validate, Serialize, Deserialize, InputObject)]
 pub struct EditProfileInput {
@@ -38,10 +43,7 @@ pub struct ProfileRegistrationInput {
     )]
     pub(super) username: String,
 
-    #[validate(
-        length(min = 8, max = 20, message = "Lenght is invalid"),
-        custom(function = "validate_query", message = "Invalid format")
-    )]
+    #[validate(custom(function = "validate_password"))]
     pub(super) password: String,
 
     #[validate(
@@ -73,13 +75,94 @@ pub struct ProfileLoginInput {
     )]
     pub(super) username: String,
 
-    #[validate(
-        length(min = 8, max = 20, message = "Lenght is invalid"),
-        custom(function = "validate_query", message = "Invalid format of password")
-    )]
+    // Пароль при входе не проверяется политикой: она могла
+    // измениться после его установки
+    #[validate(length(min = 1, max = 128, message = "Lenght is invalid"))]
     pub(super) password: String,
 }
 
+#[derive(Validate, Serialize, Deserialize, InputObject)]
+pub struct ChangePasswordInput {
+    // Текущий пароль, как и при входе, политикой не проверяется
+    #[validate(length(min = 1, max = 128, message = "Lenght is invalid"))]
+    pub(super) current_password: String,
+
+    #[validate(custom(function = "validate_password"))]
+    pub(super) new_password: String,
+}
+
+#[derive(Validate, Serialize, Deserialize, InputObject)]
+pub struct ResetPasswordInput {
+    pub(super) token: String,
+
+    #[validate(custom(function = "validate_password"))]
+    pub(super) new_password: String,
+}
+
+/// Фильтры поиска профилей.
+///
+/// Фильтры по полу и возрасту учитывают настройки приватности:
+/// профиль, скрывший поле от запрашивающего, под них не подходит.
+#[derive(Validate, Serialize, Deserialize, InputObject, Default)]
+pub struct ProfileSearchFilter {
+    /// Начало имени пользователя или имени, длина как у имени пользователя
+    #[validate(length(min = 4, max = 10, message = "Length is invalid"))]
+    pub(super) prefix: Option<String>,
+
+    pub(super) native_lang: Option<Language>,
+
+    pub(super) studied_lang: Option<Language>,
+
+    /// Минимальный уровень изучаемого языка
+    pub(super) cefr_min: Option<CefrKind>,
+
+    /// Максимальный уровень изучаемого языка
+    pub(super) cefr_max: Option<CefrKind>,
+
+    #[validate(range(min = 18, max = 99))]
+    pub(super) age_min: Option<u8>,
+
+    #[validate(range(min = 18, max = 99))]
+    pub(super) age_max: Option<u8>,
+
+    #[validate(range(min = 0, max = 1))]
+    pub(super) sex: Option<u8>,
+}
+
+impl ProfileSearchFilter {
+    /// Допустимые уровни изучаемого языка через запятую
+    pub(super) fn cefr_levels(&self) -> String {
+        let min = self.cefr_min.map_or(0, |cefr| cefr.rank());
+        let max = self.cefr_max.map_or(u8::MAX, |cefr| cefr.rank());
+
+        CefrKind::ALL
+            .iter()
+            .filter(|cefr| (min..=max).contains(&cefr.rank()))
+            .map(|cefr| cefr.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+#[derive(Serialize, Deserialize, InputObject)]
+pub struct PrivacySettingsInput {
+    pub(super) last_name: Visibility,
+    pub(super) sex: Visibility,
+    pub(super) age: Visibility,
+    pub(super) description: Visibility,
+}
+
+impl From<PrivacySettingsInput> for PrivacySettings {
+    fn from(input: PrivacySettingsInput) -> Self {
+        Self {
+            last_name: input.last_name,
+            sex: input.sex,
+            age: input.age,
+            description: input.description,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ProfileLoginOutput {
     access_token: String,
@@ -87,7 +170,7 @@ pub struct ProfileLoginOutput {
 }
 
 impl<'a> ProfileLoginOutput {
-    pub(super) fn create(access_token: String, refresh_token: String) -> Self {
+    pub(crate) fn create(access_token: String, refresh_token: String) -> Self {
         ProfileLoginOutput {
             access_token,
             refresh_token,
@@ -105,3 +188,11 @@ impl<'a> ProfileLoginOutput {
         &self.refresh_token
     }
 }
+
+/// Результат входа: пара токенов либо, при подключенной
+/// двухфакторной аутентификации, токен второго шага
+#[derive(Union)]
+pub enum LoginResult {
+    Tokens(ProfileLoginOutput),
+    Challenge(TwoFactorChallenge),
+}
@@ -1,25 +1,50 @@
 use anyhow::Result;
 use neo4rs::Node;
-use std::str::FromStr;
+use tokio::sync::OnceCell;
 use uuid::Uuid;
 
 use crate::app::core::error::CustomError;
+use crate::app::db::cypher::Label;
 
-use super::profile_model::{Permission, Profile};
-
-pub(super) const NATIVE_SPEAKER: &str = "NATIVE_SPEAKER";
-pub(super) const STUDIED: &str = "STUDIED";
+use super::profile_model::{Permission, Profile, Suspension};
+use super::profile_privacy::PrivacySettings;
 
 impl<'a> Profile {
-    pub(super) fn parse_query_resp(pnode: Node) -> Result<Profile, CustomError<'a>> {
-        let permission = match pnode
+    pub(crate) fn parse_query_resp(pnode: Node) -> Result<Profile, CustomError<'a>> {
+        let permission = Permission::from_labels(&pnode.labels());
+
+        // Профили, созданные до появления подтверждения почты,
+        // метки не имеют и считаются подтвержденными
+        let email_verified = !pnode
+            .labels()
+            .iter()
+            .any(|label| label == Label::Unverified.as_str());
+
+        let privacy = PrivacySettings::parse_node(&pnode);
+
+        let suspension = match pnode
+            .labels()
+            .iter()
+            .any(|label| label == Label::Suspended.as_str())
+        {
+            true => Some(Suspension {
+                reason: pnode.get::<String>("suspension_reason").unwrap_or_default(),
+                until: pnode.get::<i64>("suspended_until"),
+            }),
+            false => None,
+        }
+        .filter(|suspension| suspension.is_active());
+
+        let (deletion_scheduled_at, deletion_requested_by) = match pnode
             .labels()
-            .into_iter()
-            .find(|label| Permission::from_str(label).is_ok())
-            .map(|label| Permission::from_str(&label).unwrap())
+            .iter()
+            .any(|label| label == Label::PendingDeletion.as_str())
         {
-            Some(data) => data,
-            None => Permission::User,
+            true => (
+                pnode.get::<i64>("deletion_scheduled_at"),
+                pnode.get::<String>("deletion_requested_by"),
+            ),
+            false => (None, None),
         };
 
         Ok(Profile {
@@ -35,6 +60,12 @@ impl<'a> Profile {
             description: pnode.get::<String>("description"),
             created_at: pnode.get::<i64>("created_at").unwrap(),
             updated_at: pnode.get::<i64>("updated_at").unwrap(),
+            email_verified,
+            privacy,
+            suspension,
+            deletion_scheduled_at,
+            deletion_requested_by,
+            followed_by_viewer: OnceCell::new(),
         })
     }
 }
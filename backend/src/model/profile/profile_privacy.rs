@@ -0,0 +1,138 @@
+use async_graphql::{Context, Enum, Result as GraphQLResult, SimpleObject};
+use neo4rs::Node;
+use std::str::FromStr;
+use std::sync::Arc;
+use strum_macros::{Display, EnumString};
+
+use crate::app::api::security::auth::AccessClaims;
+use crate::app::core::error::CustomError;
+
+use super::profile_model::{Permission, Profile};
+use super::profile_repository::ProfileRepositoryT;
+
+/// Видимость поля профиля для других пользователей
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Enum, Display, EnumString)]
+pub enum Visibility {
+    #[strum(serialize = "PUBLIC")]
+    Public,
+
+    #[strum(serialize = "FOLLOWERS")]
+    Followers,
+
+    #[strum(serialize = "PRIVATE")]
+    Private,
+}
+
+/// Настройки приватности полей профиля.
+///
+/// Хранятся свойствами `privacy_*` узла :Profile. Владелец профиля
+/// и администраторы видят все поля независимо от настроек.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct PrivacySettings {
+    pub(crate) last_name: Visibility,
+    pub(crate) sex: Visibility,
+    pub(crate) age: Visibility,
+    pub(crate) description: Visibility,
+}
+
+impl Default for PrivacySettings {
+    fn default() -> Self {
+        Self {
+            last_name: Visibility::Public,
+            sex: Visibility::Followers,
+            age: Visibility::Followers,
+            description: Visibility::Public,
+        }
+    }
+}
+
+impl PrivacySettings {
+    /// Пары из имени свойства узла и значения настройки
+    pub(super) fn props(&self) -> [(&'static str, Visibility); 4] {
+        [
+            ("privacy_last_name", self.last_name),
+            ("privacy_sex", self.sex),
+            ("privacy_age", self.age),
+            ("privacy_description", self.description),
+        ]
+    }
+
+    /// Чтение настроек из узла.
+    /// Отсутствующие свойства заменяются значениями по умолчанию.
+    pub(super) fn parse_node(pnode: &Node) -> Self {
+        let default = Self::default();
+        let get = |prop: &str, default: Visibility| {
+            pnode
+                .get::<String>(prop)
+                .and_then(|value| Visibility::from_str(&value).ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            last_name: get("privacy_last_name", default.last_name),
+            sex: get("privacy_sex", default.sex),
+            age: get("privacy_age", default.age),
+            description: get("privacy_description", default.description),
+        }
+    }
+}
+
+impl Profile {
+    /// Запрашивающий является владельцем профиля или администратором
+    pub(super) fn viewed_by_owner(&self, ctx: &Context<'_>) -> bool {
+        match viewer(ctx) {
+            Some(claims) => {
+                claims.sub() == self.id.to_string()
+                    || claims.permission().includes(Permission::Admin)
+            }
+            None => false,
+        }
+    }
+
+    /// Значение поля, доступного только владельцу профиля и администраторам.
+    /// Остальным возвращается `None`, чтобы не обнулять списки профилей.
+    pub(super) fn owner_only<T>(&self, ctx: &Context<'_>, value: T) -> Option<T> {
+        match self.viewed_by_owner(ctx) {
+            true => Some(value),
+            false => None,
+        }
+    }
+
+    /// Проверка видимости поля с учетом настроек приватности.
+    ///
+    /// Для настройки `Followers` проверяется наличие связи
+    /// :SUBSCRIBE от запрашивающего к владельцу профиля,
+    /// результат запоминается в профиле.
+    pub(super) async fn visible(
+        &self,
+        ctx: &Context<'_>,
+        visibility: Visibility,
+    ) -> GraphQLResult<bool> {
+        if self.viewed_by_owner(ctx) {
+            return Ok(true);
+        }
+
+        match (visibility, viewer(ctx)) {
+            (Visibility::Public, _) => Ok(true),
+            (Visibility::Followers, Some(claims)) => {
+                let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+                let followed = self
+                    .followed_by_viewer
+                    .get_or_try_init(|| {
+                        profile_service.is_subscribed(claims.sub().to_string(), self.id.to_string())
+                    })
+                    .await?;
+
+                Ok(*followed)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+fn viewer<'a>(ctx: &'a Context<'_>) -> Option<&'a AccessClaims> {
+    match ctx.data_opt::<Result<Option<AccessClaims>, CustomError>>() {
+        Some(Ok(Some(claims))) => Some(claims),
+        _ => None,
+    }
+}
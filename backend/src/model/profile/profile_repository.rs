@@ -1,24 +1,73 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::Utc;
-use neo4j_cypher::entity::NodeTrait;
-use neo4j_cypher::query::{Query, QueryTrait};
 use neo4rs::{Graph, Node, RowStream};
+use std::str::FromStr;
 use std::sync::Arc;
 
-use crate::app::db::neo4j::NULL;
+use crate::app::db::cypher::{self, Cypher, Label, RelType};
 use crate::model::language::{
     language_model::{CefrKind, Language, Studied},
     language_mutation::StudiedInput,
 };
 use crate::{app::core::error::CustomError, neo4j_result};
 
-use super::profile_model::Profile;
-use super::profile_mutation::EditProfileInput;
-use super::profile_node::{NATIVE_SPEAKER, STUDIED};
+use super::profile_follows::{FollowCursor, FollowKind};
+use super::profile_model::{Permission, Profile, Suspension};
+use super::profile_mutation::{EditProfileInput, ProfileSearchFilter};
+use super::profile_privacy::PrivacySettings;
 
 type EmptyResult<'a> = Result<(), CustomError<'a>>;
 
+/// Условия поиска профилей для запросов страницы и количества.
+///
+/// Курсор и префикс задаются условиями без сравнения параметра с пустым
+/// значением, чтобы планировщик мог выбрать профили по индексам:
+/// курсор `$after` - поиском по диапазону индекса имени пользователя
+/// (на первой странице он пустой, и под условие попадает любое имя),
+/// префикс - поиском по индексам имени пользователя и имени. Условие
+/// префикса добавляется только в вариант `search_filter!(prefix)`.
+///
+/// Приватность по умолчанию для пола и возраста - FOLLOWERS,
+/// как в `PrivacySettings::default`.
+macro_rules! search_filter {
+    () => {
+        search_filter!("")
+    };
+    (prefix) => {
+        search_filter!("AND (p.username STARTS WITH $prefix OR p.first_name STARTS WITH $prefix)")
+    };
+    ($prefix_filter:literal) => {
+        concat!(
+            "
+            MATCH (p:Profile)
+            WHERE p.username > $after
+            ",
+            $prefix_filter,
+            "
+            AND NOT p:Suspended AND NOT p:Unverified AND NOT p:PendingDeletion
+            AND ($native = '' OR exists((p)-[:NATIVE_SPEAKER]->(:Language {code: $native})))
+            AND ($studied = '' OR any(
+                cefr IN [(p)-[r:STUDIED]->(:Language {code: $studied}) | r.cefr]
+                WHERE cefr IN split($levels, ',')
+            ))
+            WITH p,
+                $all_fields = 1 OR p.id = $viewer_id AS owner,
+                exists((:Profile {id: $viewer_id})-[:SUBSCRIBE]->(p)) AS follower
+            WITH p, owner OR coalesce(p.privacy_sex, 'FOLLOWERS') = 'PUBLIC'
+                    OR (coalesce(p.privacy_sex, 'FOLLOWERS') = 'FOLLOWERS' AND follower)
+                    AS sex_visible,
+                owner OR coalesce(p.privacy_age, 'FOLLOWERS') = 'PUBLIC'
+                    OR (coalesce(p.privacy_age, 'FOLLOWERS') = 'FOLLOWERS' AND follower)
+                    AS age_visible
+            WHERE ($sex < 0 OR (sex_visible AND p.sex = $sex))
+            AND ($age_min < 0 OR (age_visible AND p.age >= $age_min))
+            AND ($age_max < 0 OR (age_visible AND p.age <= $age_max))
+            "
+        )
+    };
+}
+
 #[async_trait]
 pub trait ProfileRepositoryT: Send + Sync {
     async fn create(
@@ -42,10 +91,45 @@ pub trait ProfileRepositoryT: Send + Sync {
         level: CefrKind,
     ) -> EmptyResult;
     async fn edit_profile_props(&self, input: EditProfileInput, id: String) -> EmptyResult;
+    async fn verify_email(&self, id: String) -> EmptyResult;
+    async fn set_password(&self, id: String, hash: String) -> EmptyResult;
+    async fn set_privacy(&self, id: String, privacy: PrivacySettings) -> EmptyResult;
+    async fn set_permission(&self, id: String, permission: Permission) -> EmptyResult;
+    async fn suspend(&self, id: String, suspension: Suspension) -> EmptyResult;
+    async fn unsuspend(&self, id: String) -> EmptyResult;
+    async fn schedule_deletion(
+        &self,
+        id: String,
+        scheduled_at: i64,
+        requested_by: String,
+    ) -> EmptyResult;
+    async fn cancel_deletion(&self, id: String) -> EmptyResult;
+    async fn delete(&self, id: String, scheduled_only: bool) -> Result<bool, CustomError>;
 
     async fn get_data(&self, username: String) -> Result<Profile, CustomError>;
+    async fn get_by_email(&self, email: String) -> Result<Profile, CustomError>;
     async fn get_native_langs(&self, find_by: String) -> Result<Vec<Language>, CustomError>;
     async fn get_studied_langs(&self, find_by: String) -> Result<Vec<Studied>, CustomError>;
+    async fn is_subscribed(&self, from_id: String, to_id: String) -> Result<bool, CustomError>;
+    async fn get_follows(
+        &self,
+        profile_id: String,
+        kind: FollowKind,
+        after: Option<FollowCursor>,
+        limit: usize,
+    ) -> Result<Vec<(Profile, i64)>, CustomError>;
+    async fn count_follows(&self, profile_id: String, kind: FollowKind)
+        -> Result<i64, CustomError>;
+    async fn get_suspension(&self, id: String) -> Result<Option<Suspension>, CustomError>;
+    async fn get_due_deletions(&self, now: i64) -> Result<Vec<String>, CustomError>;
+    async fn search(
+        &self,
+        filter: &ProfileSearchFilter,
+        viewer_id: String,
+        ignore_privacy: bool,
+        after: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<Profile>, i64), CustomError>;
 }
 
 pub struct ProfileRepository {
@@ -62,32 +146,135 @@ impl ProfileRepository {
 impl ProfileRepositoryT for ProfileRepository {
     /* ======================== MUTATIONS ======================== */
 
+    /// Заменить метку роли профиля
+    async fn set_permission(&self, id: String, permission: Permission) -> EmptyResult {
+        let query = Cypher::new(
+            "
+                MATCH (n:Profile) WHERE n.id = $id
+                REMOVE n:{roles}
+                SET n:{role}
+            ",
+        )
+        .labels("roles", &Label::ROLES)
+        .label("role", permission.into())
+        .build()
+        .param("id", id);
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Пометить профиль меткой :Suspended с причиной и сроком блокировки
+    async fn suspend(&self, id: String, suspension: Suspension) -> EmptyResult {
+        // Бессрочная блокировка передается нулем и хранится как null
+        let query = Cypher::new(
+            "
+                MATCH (n:Profile) WHERE n.id = $id
+                SET n:{suspended},
+                    n.suspension_reason = $reason,
+                    n.suspended_until = CASE $until WHEN 0 THEN null ELSE $until END,
+                    n.suspended_at = $suspended_at
+            ",
+        )
+        .label("suspended", Label::Suspended)
+        .build()
+        .param("id", id)
+        .param("reason", suspension.reason)
+        .param("until", suspension.until.unwrap_or(0))
+        .param("suspended_at", Utc::now().timestamp());
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Снять с профиля блокировку
+    async fn unsuspend(&self, id: String) -> EmptyResult {
+        let query = Cypher::new(
+            "
+                MATCH (n:Profile) WHERE n.id = $id
+                REMOVE n:{suspended}, n.suspension_reason, n.suspended_until, n.suspended_at
+            ",
+        )
+        .label("suspended", Label::Suspended)
+        .build()
+        .param("id", id);
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Сохранить настройки приватности полей профиля
+    async fn set_privacy(&self, id: String, privacy: PrivacySettings) -> EmptyResult {
+        let mut query = cypher::query(
+            "
+                MATCH (n:Profile) WHERE n.id = $id
+                SET n.privacy_last_name = $privacy_last_name,
+                    n.privacy_sex = $privacy_sex,
+                    n.privacy_age = $privacy_age,
+                    n.privacy_description = $privacy_description
+            ",
+        )
+        .param("id", id);
+
+        for (prop, visibility) in privacy.props() {
+            query = query.param(prop, visibility.to_string());
+        }
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Заменить хеш пароля профиля
+    async fn set_password(&self, id: String, hash: String) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (n:Profile) WHERE n.id = $id
+                SET n.hash = $hash, n.updated_at = $updated_at
+            ",
+        )
+        .param("id", id)
+        .param("hash", hash)
+        .param("updated_at", Utc::now().timestamp());
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Снять с профиля метку неподтвержденной почты
+    async fn verify_email(&self, id: String) -> EmptyResult {
+        let query = Cypher::new(
+            "
+                MATCH (n:Profile) WHERE n.id = $id
+                REMOVE n:{unverified}
+            ",
+        )
+        .label("unverified", Label::Unverified)
+        .build()
+        .param("id", id);
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
     async fn edit_profile_props(&self, input: EditProfileInput, id: String) -> EmptyResult {
-        let query = neo4rs::query(&format!(
+        // Незаполненные поля передаются пустой строкой и хранятся как null
+        let query = cypher::query(
             "
             MATCH (n:Profile) WHERE n.id = $id
 
             SET n.username = $username
             SET n.first_name = $first_name
-            SET n.last_name = {last_name}
-            SET n.description = {description}
+            SET n.last_name = CASE $last_name WHEN '' THEN null ELSE $last_name END
+            SET n.description = CASE $description WHEN '' THEN null ELSE $description END
 
             RETURN n
             ",
-            last_name = if input.last_name.is_some() {
-                format!("'{}'", input.last_name.unwrap())
-            } else {
-                NULL.to_string()
-            },
-            description = if input.description.is_some() {
-                format!("'{}'", input.description.unwrap())
-            } else {
-                NULL.to_string()
-            }
-        ))
+        )
         .param("id", id)
         .param("username", input.username)
-        .param("first_name", input.first_name);
+        .param("first_name", input.first_name)
+        .param("last_name", input.last_name.unwrap_or_default())
+        .param("description", input.description.unwrap_or_default());
 
         neo4j_result!(self.neo.run(query).await)?;
         Ok(())
@@ -100,7 +287,7 @@ impl ProfileRepositoryT for ProfileRepository {
         lang: Language,
         level: CefrKind,
     ) -> EmptyResult {
-        let query = neo4rs::query(
+        let query = cypher::query(
             "
             MATCH (p:Profile)-[r:STUDIED]->(l:Language)
             WHERE p.id = $id AND l.name = $name
@@ -122,16 +309,17 @@ impl ProfileRepositoryT for ProfileRepository {
         profile_id: String,
         lang_name: Language,
     ) -> EmptyResult {
-        match rel_type.to_uppercase().as_str() {
-            NATIVE_SPEAKER | STUDIED => {
-                let query = neo4rs::query(&format!(
+        match RelType::from_str(&rel_type) {
+            Ok(rel @ (RelType::NativeSpeaker | RelType::Studied)) => {
+                let query = Cypher::new(
                     "
-                        MATCH (p:Profile)-[r:{}]->(l:Language)
+                        MATCH (p:Profile)-[r:{rel}]->(l:Language)
                         WHERE p.id = $id AND l.name = $name
                         DELETE r
                     ",
-                    rel_type.to_uppercase()
-                ))
+                )
+                .rel("rel", rel)
+                .build()
                 .param("id", profile_id)
                 .param("name", lang_name.to_string());
 
@@ -145,7 +333,7 @@ impl ProfileRepositoryT for ProfileRepository {
 
     /// Удалить связь `:SUBSCRIBE` с указанным пользователем
     async fn unsubscribe(&self, profile_id: String, from_id: String) -> EmptyResult {
-        let query = neo4rs::query(
+        let query = cypher::query(
             "
                 MATCH (p1:Profile)-[r:SUBSCRIBE]->(p2:Profile)
                 WHERE p1.id = $id AND p2.id = $from_id
@@ -159,17 +347,108 @@ impl ProfileRepositoryT for ProfileRepository {
         Ok(())
     }
 
-    /// Создать связь `:SUBSCRIBE` с указанным пользователем
+    /// Пометить профиль меткой :PendingDeletion до окончательного удаления.
+    ///
+    /// `requested_by` - идентификатор профиля, запросившего удаление.
+    async fn schedule_deletion(
+        &self,
+        id: String,
+        scheduled_at: i64,
+        requested_by: String,
+    ) -> EmptyResult {
+        let query = Cypher::new(
+            "
+                MATCH (n:Profile) WHERE n.id = $id
+                SET n:{pending},
+                    n.deletion_requested_at = $now,
+                    n.deletion_requested_by = $requested_by,
+                    n.deletion_scheduled_at = $scheduled_at
+            ",
+        )
+        .label("pending", Label::PendingDeletion)
+        .build()
+        .param("id", id)
+        .param("now", Utc::now().timestamp())
+        .param("requested_by", requested_by)
+        .param("scheduled_at", scheduled_at);
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Снять метку :PendingDeletion
+    async fn cancel_deletion(&self, id: String) -> EmptyResult {
+        let query = Cypher::new(
+            "
+                MATCH (n:Profile) WHERE n.id = $id
+                REMOVE n:{pending}, n.deletion_requested_at, n.deletion_requested_by,
+                    n.deletion_scheduled_at
+            ",
+        )
+        .label("pending", Label::PendingDeletion)
+        .build()
+        .param("id", id);
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Окончательное удаление профиля.
+    ///
+    /// Вместе с профилем удаляются принадлежащие ему узлы: сессии
+    /// с refresh токенами, настройки двухфакторной аутентификации
+    /// с кодами восстановления, внешние учетные записи и API ключи.
+    /// Связи с языками, подписки и скрытые рекомендации удаляются
+    /// вместе с узлом. Журнал аудита не связан с профилем и сохраняется.
+    /// Переписка и уведомления в графе пока не хранятся.
+    ///
+    /// С `scheduled_only` профиль удаляется, только если срок ожидания
+    /// истек и удаление не было отменено входом. Возвращает, был ли
+    /// профиль удален.
+    async fn delete(&self, id: String, scheduled_only: bool) -> Result<bool, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (n:Profile) WHERE n.id = $id
+                AND ($scheduled_only = 0
+                    OR (n:PendingDeletion AND n.deletion_scheduled_at <= $now))
+                OPTIONAL MATCH (n)-[:HAS_SESSION|HAS_TWO_FACTOR|HAS_IDENTITY|HAS_API_KEY]->(owned)
+                OPTIONAL MATCH (owned)-[:ISSUED|HAS_RECOVERY_CODE]->(nested)
+                WITH n, collect(DISTINCT owned) AS owned, collect(DISTINCT nested) AS nested
+                FOREACH (x IN nested | DETACH DELETE x)
+                FOREACH (x IN owned | DETACH DELETE x)
+                DETACH DELETE n
+                RETURN count(*) AS deleted
+            ",
+        )
+        .param("id", id)
+        .param("scheduled_only", scheduled_only as i64)
+        .param("now", Utc::now().timestamp());
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        if let Ok(Some(row)) = result.next().await {
+            return Ok(row.get::<i64>("deleted").unwrap_or(0) > 0);
+        }
+
+        Ok(false)
+    }
+
+    /// Создать связь `:SUBSCRIBE` с указанным пользователем.
+    ///
+    /// Повторная подписка ничего не меняет и сохраняет
+    /// время первой подписки.
     async fn subscribe(&self, to_id: String, from_id: String) -> EmptyResult {
-        let query = neo4rs::query(
+        let query = cypher::query(
             "
-                MATCH (e:Profile) WHERE e.id = $form
+                MATCH (e:Profile) WHERE e.id = $from
                 MATCH (d:Profile) WHERE d.id = $to OR d.username = $to OR d.email = $to
+                WITH e, d WHERE e <> d
 
-                CREATE (e)-[:SUBSCRIBE {timestamp: $timestamp}]->(d)
+                MERGE (e)-[r:SUBSCRIBE]->(d)
+                ON CREATE SET r.timestamp = $timestamp
             ",
         )
-        .param("form", from_id)
+        .param("from", from_id)
         .param("to", to_id)
         .param("timestamp", Utc::now().timestamp());
 
@@ -185,13 +464,14 @@ impl ProfileRepositoryT for ProfileRepository {
     ) -> EmptyResult {
         let txn = self.neo.start_txn().await?;
 
-        neo4j_result!(
-            txn.run_queries(vec![
-                create_user_query(&profile),
-                set_relationships_query(profile.id.to_string(), native_langs, studied_langs)
-            ])
-            .await
-        )?;
+        let mut queries = vec![create_user_query(&profile)];
+        queries.extend(set_relationships_query(
+            profile.id.to_string(),
+            native_langs,
+            studied_langs,
+        ));
+
+        neo4j_result!(txn.run_queries(queries).await)?;
 
         Ok(neo4j_result!(txn.commit().await)?)
     }
@@ -199,7 +479,7 @@ impl ProfileRepositoryT for ProfileRepository {
     /* ======================== QUERYS ======================== */
 
     async fn get_data(&self, find_by: String) -> Result<Profile, CustomError> {
-        let query = neo4rs::query(
+        let query = cypher::query(
             "MATCH (n:Profile)
             WHERE n.id = $value OR n.username = $value OR n.email = $value
             RETURN n",
@@ -210,8 +490,24 @@ impl ProfileRepositoryT for ProfileRepository {
         Ok(get_user_query(result).await?)
     }
 
+    /// Поиск профиля только по адресу почты.
+    ///
+    /// В отличие от `get_data` не совпадает с профилем,
+    /// имя пользователя которого равно искомому адресу.
+    async fn get_by_email(&self, email: String) -> Result<Profile, CustomError> {
+        let query = cypher::query(
+            "MATCH (n:Profile)
+            WHERE n.email = $email
+            RETURN n",
+        )
+        .param("email", email);
+        let result = neo4j_result!(self.neo.execute(query).await)?;
+
+        Ok(get_user_query(result).await?)
+    }
+
     async fn get_native_langs(&self, find_by: String) -> Result<Vec<Language>, CustomError> {
-        let query = neo4rs::query(
+        let query = cypher::query(
             "MATCH (n:Profile)-[r:NATIVE_SPEAKER]-(l)
             WHERE n.id = $value OR n.username = $value OR n.email = $value
             RETURN r, n, l",
@@ -236,7 +532,7 @@ impl ProfileRepositoryT for ProfileRepository {
     }
 
     async fn get_studied_langs(&self, find_by: String) -> Result<Vec<Studied>, CustomError> {
-        let query = neo4rs::query(
+        let query = cypher::query(
             "MATCH (n:Profile)-[r:STUDIED]-(l)
             WHERE n.id = $value OR n.username = $value OR n.email = $value
             RETURN r, n, l",
@@ -260,6 +556,287 @@ impl ProfileRepositoryT for ProfileRepository {
 
         Ok(output)
     }
+
+    /// Проверка наличия связи `:SUBSCRIBE` от одного профиля к другому
+    async fn is_subscribed(&self, from_id: String, to_id: String) -> Result<bool, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (p1:Profile) WHERE p1.id = $from_id
+                MATCH (p2:Profile) WHERE p2.id = $to_id
+                RETURN exists((p1)-[:SUBSCRIBE]->(p2)) AS subscribed
+            ",
+        )
+        .param("from_id", from_id)
+        .param("to_id", to_id);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        if let Ok(Some(row)) = result.next().await {
+            return Ok(row.get::<bool>("subscribed").unwrap_or(false));
+        }
+
+        Ok(false)
+    }
+
+    /// Страница списка подписок профиля, упорядоченная от новых к старым.
+    ///
+    /// Возвращает профили со временем подписки. Повторные связи,
+    /// созданные до перехода на `MERGE`, учитываются один раз
+    /// по времени первой подписки. Профили, ожидающие удаления,
+    /// не выводятся.
+    async fn get_follows(
+        &self,
+        profile_id: String,
+        kind: FollowKind,
+        after: Option<FollowCursor>,
+        limit: usize,
+    ) -> Result<Vec<(Profile, i64)>, CustomError> {
+        let query = match kind {
+            FollowKind::Followers => cypher::query(
+                "
+                    MATCH (p:Profile)<-[r:SUBSCRIBE]-(n:Profile)
+                    WHERE p.id = $id AND NOT n:PendingDeletion
+                    WITH n, min(coalesce(r.timestamp, 0)) AS followed_at
+                    WHERE $after_id = '' OR followed_at < $after_ts
+                        OR (followed_at = $after_ts AND n.id > $after_id)
+                    RETURN n, followed_at
+                    ORDER BY followed_at DESC, n.id
+                    LIMIT $limit
+                ",
+            ),
+            FollowKind::Following => cypher::query(
+                "
+                    MATCH (p:Profile)-[r:SUBSCRIBE]->(n:Profile)
+                    WHERE p.id = $id AND NOT n:PendingDeletion
+                    WITH n, min(coalesce(r.timestamp, 0)) AS followed_at
+                    WHERE $after_id = '' OR followed_at < $after_ts
+                        OR (followed_at = $after_ts AND n.id > $after_id)
+                    RETURN n, followed_at
+                    ORDER BY followed_at DESC, n.id
+                    LIMIT $limit
+                ",
+            ),
+            // Взаимная подписка возникает в момент ответной подписки
+            FollowKind::Mutual => cypher::query(
+                "
+                    MATCH (p:Profile)-[r1:SUBSCRIBE]->(n:Profile)-[r2:SUBSCRIBE]->(p)
+                    WHERE p.id = $id AND NOT n:PendingDeletion
+                    WITH n,
+                        min(coalesce(r1.timestamp, 0)) AS since_mine,
+                        min(coalesce(r2.timestamp, 0)) AS since_theirs
+                    WITH n, CASE WHEN since_mine > since_theirs
+                        THEN since_mine ELSE since_theirs END AS followed_at
+                    WHERE $after_id = '' OR followed_at < $after_ts
+                        OR (followed_at = $after_ts AND n.id > $after_id)
+                    RETURN n, followed_at
+                    ORDER BY followed_at DESC, n.id
+                    LIMIT $limit
+                ",
+            ),
+        };
+
+        // Отсутствие курсора передается пустым идентификатором
+        let (after_ts, after_id) =
+            after.map_or((0, String::new()), |after| (after.followed_at, after.id));
+
+        let query = query
+            .param("id", profile_id)
+            .param("after_ts", after_ts)
+            .param("after_id", after_id)
+            .param("limit", limit as i64);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+        let mut output: Vec<(Profile, i64)> = Vec::new();
+
+        while let Ok(Some(row)) = result.next().await {
+            if let Some(pnode) = row.get::<Node>("n") {
+                output.push((
+                    Profile::parse_query_resp(pnode)?,
+                    row.get::<i64>("followed_at").unwrap_or(0),
+                ));
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Количество профилей в списке подписок
+    async fn count_follows(
+        &self,
+        profile_id: String,
+        kind: FollowKind,
+    ) -> Result<i64, CustomError> {
+        let query = match kind {
+            FollowKind::Followers => cypher::query(
+                "
+                    MATCH (p:Profile)<-[:SUBSCRIBE]-(n:Profile)
+                    WHERE p.id = $id AND NOT n:PendingDeletion
+                    RETURN count(DISTINCT n) AS count
+                ",
+            ),
+            FollowKind::Following => cypher::query(
+                "
+                    MATCH (p:Profile)-[:SUBSCRIBE]->(n:Profile)
+                    WHERE p.id = $id AND NOT n:PendingDeletion
+                    RETURN count(DISTINCT n) AS count
+                ",
+            ),
+            FollowKind::Mutual => cypher::query(
+                "
+                    MATCH (p:Profile)-[:SUBSCRIBE]->(n:Profile)-[:SUBSCRIBE]->(p)
+                    WHERE p.id = $id AND NOT n:PendingDeletion
+                    RETURN count(DISTINCT n) AS count
+                ",
+            ),
+        }
+        .param("id", profile_id);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        if let Ok(Some(row)) = result.next().await {
+            return Ok(row.get::<i64>("count").unwrap_or(0));
+        }
+
+        Ok(0)
+    }
+
+    /// Получение действующей блокировки профиля
+    async fn get_suspension(&self, id: String) -> Result<Option<Suspension>, CustomError> {
+        let query = Cypher::new(
+            "
+                MATCH (n:Profile:{suspended}) WHERE n.id = $id
+                AND (n.suspended_until IS NULL OR n.suspended_until > $now)
+                RETURN n.suspension_reason AS reason, n.suspended_until AS until
+            ",
+        )
+        .label("suspended", Label::Suspended)
+        .build()
+        .param("id", id)
+        .param("now", Utc::now().timestamp());
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        if let Ok(Some(row)) = result.next().await {
+            return Ok(Some(Suspension {
+                reason: row.get::<String>("reason").unwrap_or_default(),
+                until: row.get::<i64>("until"),
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// Идентификаторы профилей, срок ожидания удаления которых истек
+    async fn get_due_deletions(&self, now: i64) -> Result<Vec<String>, CustomError> {
+        let query = Cypher::new(
+            "
+                MATCH (n:Profile:{pending}) WHERE n.deletion_scheduled_at <= $now
+                RETURN n.id AS id
+            ",
+        )
+        .label("pending", Label::PendingDeletion)
+        .build()
+        .param("now", now);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+        let mut output: Vec<String> = Vec::new();
+
+        while let Ok(Some(row)) = result.next().await {
+            if let Some(id) = row.get::<String>("id") {
+                output.push(id);
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Поиск профилей по фильтрам, упорядоченный по имени пользователя.
+    ///
+    /// Возвращает страницу после имени пользователя `after`
+    /// и общее количество подходящих профилей. Количество считается
+    /// отдельным запросом, страница выбирается по индексу имени.
+    async fn search(
+        &self,
+        filter: &ProfileSearchFilter,
+        viewer_id: String,
+        ignore_privacy: bool,
+        after: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<Profile>, i64), CustomError> {
+        let text = match filter.prefix.is_some() {
+            true => concat!(
+                search_filter!(prefix),
+                "RETURN p ORDER BY p.username LIMIT $limit"
+            ),
+            false => concat!(
+                search_filter!(),
+                "RETURN p ORDER BY p.username LIMIT $limit"
+            ),
+        };
+
+        let query = search_params(cypher::query(text), filter, &viewer_id, ignore_privacy)
+            .param("after", after.unwrap_or_default())
+            .param("limit", limit as i64);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+        let mut profiles: Vec<Profile> = Vec::new();
+
+        while let Ok(Some(row)) = result.next().await {
+            if let Some(pnode) = row.get::<Node>("p") {
+                profiles.push(Profile::parse_query_resp(pnode)?);
+            }
+        }
+
+        let text = match filter.prefix.is_some() {
+            true => concat!(search_filter!(prefix), "RETURN count(p) AS total"),
+            false => concat!(search_filter!(), "RETURN count(p) AS total"),
+        };
+
+        let query = search_params(cypher::query(text), filter, &viewer_id, ignore_privacy)
+            .param("after", "");
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+        let mut total = 0;
+
+        if let Ok(Some(row)) = result.next().await {
+            total = row.get::<i64>("total").unwrap_or(0);
+        }
+
+        Ok((profiles, total))
+    }
+}
+
+/// Общие параметры запросов поиска профилей.
+///
+/// Незаданные фильтры передаются пустой строкой или -1.
+fn search_params(
+    query: neo4rs::Query,
+    filter: &ProfileSearchFilter,
+    viewer_id: &str,
+    ignore_privacy: bool,
+) -> neo4rs::Query {
+    query
+        .param("prefix", filter.prefix.clone().unwrap_or_default())
+        .param(
+            "native",
+            filter
+                .native_lang
+                .map(|lang| lang.to_string())
+                .unwrap_or_default(),
+        )
+        .param(
+            "studied",
+            filter
+                .studied_lang
+                .map(|lang| lang.to_string())
+                .unwrap_or_default(),
+        )
+        .param("levels", filter.cefr_levels())
+        .param("sex", filter.sex.map_or(-1, i64::from))
+        .param("age_min", filter.age_min.map_or(-1, i64::from))
+        .param("age_max", filter.age_max.map_or(-1, i64::from))
+        .param("viewer_id", viewer_id)
+        .param("all_fields", ignore_privacy as i64)
 }
 
 async fn get_user_query<'a>(mut result: RowStream) -> Result<Profile, CustomError<'a>> {
@@ -276,13 +853,43 @@ async fn get_user_query<'a>(mut result: RowStream) -> Result<Profile, CustomErro
     }
 }
 
-/// Вспомогательная функция для формирования запроса на создание узла пользователя
+/// Вспомогательная функция для формирования запроса на создание узла пользователя.
+///
+/// Новый профиль помечается меткой :Unverified до подтверждения почты
 fn create_user_query(profile: &Arc<Profile>) -> neo4rs::Query {
-    neo4rs::query(
-        &Query::init()
-            .create(vec![&profile.node("n").into()])
-            .finalize(),
+    Cypher::new(
+        "
+            CREATE (n:{labels} {
+                id: $id,
+                email: $email,
+                hash: $hash,
+                username: $username,
+                first_name: $first_name,
+                last_name: CASE $last_name WHEN '' THEN null ELSE $last_name END,
+                sex: $sex,
+                age: $age,
+                description: CASE $description WHEN '' THEN null ELSE $description END,
+                created_at: $created_at,
+                updated_at: $updated_at
+            })
+        ",
+    )
+    .labels("labels", &[Label::Profile, Label::Unverified])
+    .build()
+    .param("id", profile.id.to_string())
+    .param("email", profile.email.clone())
+    .param("hash", profile.hash.clone())
+    .param("username", profile.username.clone())
+    .param("first_name", profile.first_name.clone())
+    .param("last_name", profile.last_name.clone().unwrap_or_default())
+    .param("sex", profile.sex as i64)
+    .param("age", profile.age as i64)
+    .param(
+        "description",
+        profile.description.clone().unwrap_or_default(),
     )
+    .param("created_at", profile.created_at)
+    .param("updated_at", profile.updated_at)
 }
 
 /// Вспомогательная функция в отвечающая за создание связей с языковыми узлами
@@ -294,83 +901,41 @@ fn create_user_query(profile: &Arc<Profile>) -> neo4rs::Query {
 /// Связь типа NATIVE_SPEAKER не содержит параметров.
 /// Связь типа STUDIED а параметрах содержит параметр прогресса в изучении.
 /// Прогресс в изучении поределяется по шкале CEFR.
+///
+/// Для каждой связи формируется отдельный запрос, они выполняются
+/// в одной транзакции с созданием профиля.
 fn set_relationships_query(
     profile_id: String,
     native_langs: Vec<Language>,
     studied_langs: Vec<StudiedInput>,
-) -> neo4rs::Query {
-    // Получение пользователя которому необходимо установить отношения
-    let mut base = format!("MATCH (p:Profile) WHERE p.id = '{}'\n", profile_id);
-    let mut create_query = String::from("\n\nCREATE");
-
-    let (studied_match_query, studied_create_query) = studied_languages_pquery(
-        &studied_langs
-            .into_iter()
-            .map(|language| language.into())
-            .collect::<Vec<Studied>>(),
-    );
-    let (native_match_query, native_create_query) = native_languages_pquery(&native_langs);
-
-    // Объединение запросов создания связей
-    create_query.push_str(&studied_create_query);
-    create_query.push_str(&native_create_query);
-    // Удаление последнего символа
-    create_query.pop();
-
-    // Объединение в финальный запрос
-    base.push_str(&studied_match_query);
-    base.push_str(&native_match_query);
-    base.push_str(&create_query);
-
-    neo4rs::Query::new(base)
-}
-
-fn studied_languages_pquery(studied_languages: &Vec<Studied>) -> (String, String) {
-    let mut create_query = String::new();
-
-    // Получение запрашиваемых языковых узлов
-    let match_query = studied_languages
-        .iter()
-        .map(|item| {
-            // Формирование запроса на создание связи
-            create_query.push_str(&format!(
-                " (p)-[:STUDIED {{cefr: '{}' }}]->(language_{}),",
-                item.cefr, item.lang
-            ));
-
-            // Формирование запроса для получения нужного языкового узла
-            format!(
-                "\nMATCH (language_{}:Language) WHERE language_{}.code = '{}'",
-                item.lang, item.lang, item.lang
+) -> Vec<neo4rs::Query> {
+    let studied = studied_langs
+        .into_iter()
+        .map(|language| -> Studied { language.into() })
+        .map(|studied| {
+            cypher::query(
+                "
+                    MATCH (p:Profile) WHERE p.id = $id
+                    MATCH (l:Language) WHERE l.code = $code
+                    CREATE (p)-[:STUDIED {cefr: $cefr}]->(l)
+                ",
             )
-        })
-        .collect::<String>();
+            .param("id", profile_id.clone())
+            .param("code", studied.lang.to_string())
+            .param("cefr", studied.cefr.to_string())
+        });
 
-    (match_query, create_query)
-}
-
-fn native_languages_pquery(native_languages: &Vec<Language>) -> (String, String) {
-    let mut create_query = String::new();
-
-    // Получение запрашиваемых языковых узлов
-    let match_query = native_languages
-        .iter()
-        .map(|item| {
-            // Формирование запроса на создание связи
-            create_query.push_str(&format!(
-                " (p)-[:NATIVE_SPEAKER]->(language_{}),",
-                item.to_string()
-            ));
-
-            // Формирование запроса для получения нужного языкового узла
-            format!(
-                "\nMATCH (language_{}:Language) WHERE language_{}.code = '{}'",
-                item.to_string(),
-                item.to_string(),
-                item.to_string()
-            )
-        })
-        .collect::<String>();
+    let native = native_langs.into_iter().map(|language| {
+        cypher::query(
+            "
+                MATCH (p:Profile) WHERE p.id = $id
+                MATCH (l:Language) WHERE l.code = $code
+                CREATE (p)-[:NATIVE_SPEAKER]->(l)
+            ",
+        )
+        .param("id", profile_id.clone())
+        .param("code", language.to_string())
+    });
 
-    (match_query, create_query)
+    studied.chain(native).collect()
 }
@@ -1,9 +1,20 @@
-use async_graphql::{Context, Object, Result as GraphQLResult};
+use async_graphql::connection::{query, Connection, Edge};
+use async_graphql::{Context, Error as GraphQLError, Object, Result as GraphQLResult};
 use std::sync::Arc;
 use validator::Validate;
 
-use crate::app::api::security::auth::{self, AccessClaims, Token};
-use crate::app::core::error::CustomError;
+use crate::app::api::graphql::limits::{page_size, truncate_page, DB_COST, LIST_SIZE};
+use crate::app::api::security::auth::{
+    self, get_access_claims, ActionClaims, EmailVerifiedGuard, Token, TokenPurpose,
+};
+use crate::app::api::security::pow::{ChallengeSolution, ProofOfWork, RegistrationChallenge};
+use crate::app::api::security::{client::ClientInfo, lockout::LoginLockout, scope::Scope};
+use crate::app::core::error::{
+    CustomError,
+    CustomErrorKind::{self, InvalidCredentials, TokenInvalid},
+};
+use crate::app::mailer::{templates, MailerT};
+use crate::model::audit::audit_model::{audit, AuditEntry, AuditEvent};
 use crate::model::language::language_model::Studied;
 use crate::model::language::{
     language_error::{ERR_LANG__DUBLICATED, ERR_LANG__UNIQUE},
@@ -11,14 +22,23 @@ use crate::model::language::{
     language_mutation::StudiedInput,
 };
 use crate::model::profile::{
-    profile_error::ERR_PROF__SELF_SUBSCRIBE,
-    profile_model::{Permission, Profile},
+    profile_error::{ERR_PROF__EMAIL_ALREADY_VERIFIED, ERR_PROF__SELF_SUBSCRIBE},
+    profile_model::{
+        Permission, Profile, SearchTotal, DELETION_GRACE_DAYS, SEARCH_PAGE_MAX, SEARCH_PAGE_SIZE,
+    },
     profile_mutation::{
-        EditProfileInput, ProfileLoginInput, ProfileLoginOutput, ProfileRegistrationInput,
+        ChangePasswordInput, EditProfileInput, LoginResult, PrivacySettingsInput,
+        ProfileLoginInput, ProfileRegistrationInput, ProfileSearchFilter, ResetPasswordInput,
     },
     profile_repository::ProfileRepositoryT,
     profile_resolver::auth::AuthGuard,
 };
+use crate::model::token::token_model::{check_lockout, complete_login, revoke_all_sessions};
+
+/// Срок действия ссылки подтверждения почты
+const VERIFY_EMAIL_HOURS: i64 = 24;
+/// Срок действия ссылки сброса пароля
+const RESET_PASSWORD_MINUTES: i64 = 30;
 
 #[derive(Default)]
 pub struct ProfileMutation;
@@ -26,54 +46,334 @@ pub struct ProfileMutation;
 #[Object]
 impl<'a> ProfileMutation {
     /// Метод регистрации.
+    ///
+    /// После создания профиля на указанную почту отправляется
+    /// ссылка подтверждения. Ошибка отправки не отменяет регистрацию,
+    /// письмо можно запросить повторно.
+    ///
+    /// Требуется решение задачи из `registrationChallenge`.
     async fn registration(
         &'a self,
         ctx: &'a Context<'_>,
         profile_input: ProfileRegistrationInput,
         #[graphql(validator(min_items = 1, max_items = 2))] native_langs_input: Vec<Language>,
         #[graphql(validator(min_items = 1, max_items = 4))] studied_langs_input: Vec<StudiedInput>,
+        challenge: ChallengeSolution,
     ) -> GraphQLResult<&str> {
         reg_validation(&profile_input, &native_langs_input, &studied_langs_input)?;
 
+        let ip = ctx.data_opt::<ClientInfo>().and_then(|client| client.ip());
+        let proof_of_work = ctx.data::<Arc<ProofOfWork>>()?;
+        let solved = proof_of_work.check(ip, &challenge)?;
+
         let profile = Arc::new(Profile::new(profile_input)?);
         let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
 
         profile_service
-            .create(profile, native_langs_input, studied_langs_input)
+            .create(profile.clone(), native_langs_input, studied_langs_input)
             .await?;
 
+        // Задача расходуется только на успешную регистрацию
+        proof_of_work.commit(ip, solved);
+
+        let profile_id = profile.id.to_string();
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::Registration)
+                .actor(&profile_id)
+                .target(&profile_id),
+        )
+        .await;
+
+        if let Err(err) = send_verification_email(ctx, &profile).await {
+            log::warn!("Failed to send verification email: {:?}", err);
+        }
+
+        Ok("OK")
+    }
+
+    /// Метод подтверждения адреса электронной почты по токену из письма.
+    async fn verify_email(&'a self, ctx: &'a Context<'_>, token: String) -> GraphQLResult<&str> {
+        let claims = ActionClaims::decode(&token, TokenPurpose::VerifyEmail)?;
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+
+        let profile = profile_service.get_data(claims.sub().to_string()).await?;
+
+        // Токен выпущен для адреса, который с тех пор был изменен
+        if claims.data() != Some(profile.email.as_str()) {
+            return Err(CustomError::new().kind(TokenInvalid).build().into());
+        }
+
+        if !profile.email_verified {
+            profile_service
+                .verify_email(claims.sub().to_string())
+                .await?;
+        }
+
+        Ok("OK")
+    }
+
+    /// Метод повторной отправки ссылки подтверждения почты.
+    #[graphql(guard = "AuthGuard::at_least(Permission::User)")]
+    async fn resend_verification_email(&'a self, ctx: &'a Context<'_>) -> GraphQLResult<&str> {
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let access_claims = get_access_claims(ctx);
+
+        let profile = profile_service
+            .get_data(access_claims.sub().to_string())
+            .await?;
+
+        if profile.email_verified {
+            return Err(crate::unprocessable!(
+                "email",
+                Some(ERR_PROF__EMAIL_ALREADY_VERIFIED.to_string())
+            )
+            .into());
+        }
+
+        send_verification_email(ctx, &profile).await?;
+
         Ok("OK")
     }
 
     /// Метод авторизации.
     /// В ответ клиент должен получить набор токенов.
+    ///
+    /// Неудачные попытки входа считаются по профилю и по IP адресу,
+    /// после их исчерпания вход временно блокируется.
+    ///
+    /// При подключенной двухфакторной аутентификации вместо токенов
+    /// возвращается токен второго шага для `loginSecondFactor`.
     async fn login(
         &'a self,
         ctx: &'a Context<'_>,
         login_input: ProfileLoginInput,
-    ) -> GraphQLResult<ProfileLoginOutput> {
+    ) -> GraphQLResult<LoginResult> {
         login_input.validate()?;
 
         let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
-        let profile = profile_service.get_data(login_input.username).await?;
+        let lockout = ctx.data::<Arc<LoginLockout>>()?;
+        let ip = ctx.data_opt::<ClientInfo>().and_then(|client| client.ip());
+
+        check_lockout(ctx, None).await?;
+
+        let profile = match profile_service.get_data(login_input.username).await {
+            Ok(profile) => profile,
+            Err(err) if matches!(err.kind(), CustomErrorKind::NotFound(_)) => {
+                lockout.failure(None, ip);
+                audit(
+                    ctx,
+                    AuditEntry::new(AuditEvent::LoginFailed).details("Unknown username"),
+                )
+                .await;
+                return Err(CustomError::new().kind(InvalidCredentials).build().into());
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let profile_id = profile.id.to_string();
+        check_lockout(ctx, Some(&profile_id)).await?;
+
+        if !profile.verify_password(&login_input.password)? {
+            lockout.failure(Some(&profile_id), ip);
+            audit(
+                ctx,
+                AuditEntry::new(AuditEvent::LoginFailed)
+                    .target(&profile_id)
+                    .details("Invalid password"),
+            )
+            .await;
+            return Err(CustomError::new().kind(InvalidCredentials).build().into());
+        }
 
-        let access_token = Token::encode(auth::AccessClaims::new(
-            profile.id.to_string(),
-            profile.permission,
-            chrono::Duration::minutes(15),
-        ))?;
-        let refresh_token = Token::encode(auth::RefreshClaims::new(
+        let result = complete_login(ctx, &profile).await?;
+
+        // При двухфакторной аутентификации счетчик сбрасывается после второго шага
+        if matches!(result, LoginResult::Tokens(_)) {
+            lockout.success(&profile_id);
+        }
+
+        Ok(result)
+    }
+
+    /// Метод смены пароля.
+    ///
+    /// Требует текущий пароль, неверные пароли учитываются так же,
+    /// как при входе. После смены все сессии профиля завершаются,
+    /// включая текущую.
+    #[graphql(guard = "AuthGuard::at_least(Permission::User).and(AuthGuard::session())")]
+    async fn change_password(
+        &'a self,
+        ctx: &'a Context<'_>,
+        input: ChangePasswordInput,
+    ) -> GraphQLResult<&str> {
+        input.validate().map_err(CustomError::from)?;
+
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let lockout = ctx.data::<Arc<LoginLockout>>()?;
+        let ip = ctx.data_opt::<ClientInfo>().and_then(|client| client.ip());
+        let access_claims = get_access_claims(ctx);
+
+        check_lockout(ctx, Some(access_claims.sub())).await?;
+
+        let profile = profile_service
+            .get_data(access_claims.sub().to_string())
+            .await?;
+
+        if !profile.verify_password(&input.current_password)? {
+            lockout.failure(Some(access_claims.sub()), ip);
+            return Err(CustomError::new().kind(InvalidCredentials).build().into());
+        }
+
+        lockout.success(access_claims.sub());
+
+        profile_service
+            .set_password(
+                profile.id.to_string(),
+                Profile::hash_password(&input.new_password)?,
+            )
+            .await?;
+        revoke_all_sessions(ctx, profile.id.to_string()).await?;
+
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::PasswordChanged).target(&profile.id.to_string()),
+        )
+        .await;
+
+        Ok("OK")
+    }
+
+    /// Метод удаления профиля.
+    ///
+    /// Требует текущий пароль. Профиль деактивируется: сессии
+    /// завершаются, профиль скрывается из поиска и выдачи. Через
+    /// `ACCOUNT_DELETION_GRACE_DAYS` дней профиль удаляется
+    /// окончательно, вход до этого срока отменяет удаление.
+    #[graphql(guard = "AuthGuard::at_least(Permission::User).and(AuthGuard::session())")]
+    async fn delete_account(
+        &'a self,
+        ctx: &'a Context<'_>,
+        password: String,
+    ) -> GraphQLResult<&str> {
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let access_claims = get_access_claims(ctx);
+
+        let profile = profile_service
+            .get_data(access_claims.sub().to_string())
+            .await?;
+
+        if !profile.verify_password(&password)? {
+            return Err(CustomError::new().kind(InvalidCredentials).build().into());
+        }
+
+        let scheduled_at =
+            (chrono::Utc::now() + chrono::Duration::days(*DELETION_GRACE_DAYS)).timestamp();
+
+        // Сессии завершаются до пометки профиля, иначе обновление
+        // токенов в промежутке отменило бы удаление
+        revoke_all_sessions(ctx, profile.id.to_string()).await?;
+        profile_service
+            .schedule_deletion(profile.id.to_string(), scheduled_at, profile.id.to_string())
+            .await?;
+
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::ProfileDeletionScheduled)
+                .target(&profile.id.to_string())
+                .details(&scheduled_at.to_string()),
+        )
+        .await;
+
+        Ok("OK")
+    }
+
+    /// Метод запроса сброса пароля.
+    ///
+    /// Ссылка сброса отправляется на почту профиля. Ответ не зависит
+    /// от существования профиля, чтобы метод нельзя было использовать
+    /// для перебора зарегистрированных адресов.
+    async fn request_password_reset(
+        &'a self,
+        ctx: &'a Context<'_>,
+        email: String,
+    ) -> GraphQLResult<&str> {
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let mailer = ctx.data::<Arc<dyn MailerT>>()?;
+
+        let profile = match profile_service.get_data(email.clone()).await {
+            Ok(profile) if profile.email == email => profile,
+            Ok(_) => return Ok("OK"),
+            Err(err) if matches!(err.kind(), CustomErrorKind::NotFound(_)) => return Ok("OK"),
+            Err(err) => return Err(err.into()),
+        };
+
+        let token = Token::encode(ActionClaims::new(
             profile.id.to_string(),
-            chrono::Duration::days(7),
-        ))?;
+            TokenPurpose::ResetPassword,
+            Some(profile.password_fingerprint()),
+            chrono::Duration::minutes(RESET_PASSWORD_MINUTES),
+        ))
+        .map_err(|err| crate::internal!(&err.to_string()))?;
+
+        if let Err(err) = mailer
+            .send(templates::reset_password(&profile.email, &token))
+            .await
+        {
+            log::warn!("Failed to send password reset email: {:?}", err);
+        }
 
-        Ok(ProfileLoginOutput::create(access_token, refresh_token))
+        Ok("OK")
+    }
+
+    /// Метод установки нового пароля по токену из письма.
+    ///
+    /// Токен одноразовый: он привязан к текущему хешу пароля
+    /// и перестает действовать после его смены. Все сессии
+    /// профиля завершаются, блокировка входа снимается.
+    async fn reset_password(
+        &'a self,
+        ctx: &'a Context<'_>,
+        input: ResetPasswordInput,
+    ) -> GraphQLResult<&str> {
+        input.validate().map_err(CustomError::from)?;
+
+        let claims = ActionClaims::decode(&input.token, TokenPurpose::ResetPassword)?;
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+
+        let profile = profile_service.get_data(claims.sub().to_string()).await?;
+
+        if claims.data() != Some(profile.password_fingerprint().as_str()) {
+            return Err(CustomError::new().kind(TokenInvalid).build().into());
+        }
+
+        profile_service
+            .set_password(
+                profile.id.to_string(),
+                Profile::hash_password(&input.new_password)?,
+            )
+            .await?;
+        revoke_all_sessions(ctx, profile.id.to_string()).await?;
+
+        // Владелец почты подтвердил доступ к профилю,
+        // блокировка входа после неудачных попыток снимается
+        ctx.data::<Arc<LoginLockout>>()?
+            .success(&profile.id.to_string());
+
+        let profile_id = profile.id.to_string();
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::PasswordReset)
+                .actor(&profile_id)
+                .target(&profile_id),
+        )
+        .await;
+
+        Ok("OK")
     }
 
     /// Метод удаления связи :SUBSCRIBE между двумя узлами типа :Profile
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
+    #[graphql(guard = "AuthGuard::scope(Scope::ProfileWrite)")]
     async fn unsubscribe(&'a self, ctx: &'a Context<'_>, from_id: String) -> GraphQLResult<&str> {
         let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
         let access_claims = get_access_claims(ctx);
@@ -85,10 +385,10 @@ impl<'a> ProfileMutation {
         Ok("OK")
     }
 
-    /// Метод установки связи :SUBSCRIBE между двумя узлами типа :Profile
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
+    /// Метод установки связи :SUBSCRIBE между двумя узлами типа :Profile.
+    /// Доступен только профилям с подтвержденной почтой.
+    /// Повторная подписка не создает новую связь.
+    #[graphql(guard = "AuthGuard::scope(Scope::ProfileWrite).and(EmailVerifiedGuard)")]
     async fn subscribe(&'a self, ctx: &'a Context<'_>, to_id: String) -> GraphQLResult<&str> {
         let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
         let access_claims = get_access_claims(ctx);
@@ -108,9 +408,7 @@ impl<'a> ProfileMutation {
     ///
     /// В качестве параметра должно передаваться тип связи между
     /// двумя указанными узлами и параметр `name` для узла :Language.
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
+    #[graphql(guard = "AuthGuard::scope(Scope::ProfileWrite)")]
     async fn remove_lang_rel(
         &'a self,
         ctx: &'a Context<'_>,
@@ -121,15 +419,21 @@ impl<'a> ProfileMutation {
         let access_claims = get_access_claims(ctx);
 
         profile_service
-            .remove_language(rel_type, access_claims.sub().to_string(), lang)
+            .remove_language(rel_type.clone(), access_claims.sub().to_string(), lang)
             .await?;
 
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::LanguageRemoved)
+                .target(access_claims.sub())
+                .details(&format!("{} {}", rel_type, lang)),
+        )
+        .await;
+
         Ok("OK")
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
+    #[graphql(guard = "AuthGuard::scope(Scope::ProfileWrite)")]
     async fn edit_lang_level(
         &'a self,
         ctx: &'a Context<'_>,
@@ -143,12 +447,18 @@ impl<'a> ProfileMutation {
             .edit_lang_level(access_claims.sub().to_string(), lang, new_level)
             .await?;
 
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::LanguageLevelChanged)
+                .target(access_claims.sub())
+                .details(&format!("{} {}", lang, new_level)),
+        )
+        .await;
+
         Ok("OK")
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-    .or(AuthGuard::new(Permission::Developer))
-    .or(AuthGuard::new(Permission::User))")]
+    #[graphql(guard = "AuthGuard::scope(Scope::ProfileWrite)")]
     async fn edit_profile_info(
         &'a self,
         ctx: &'a Context<'_>,
@@ -161,6 +471,39 @@ impl<'a> ProfileMutation {
             .edit_profile_props(input, access_claims.sub().to_string())
             .await?;
 
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::ProfileEdited)
+                .target(access_claims.sub())
+                .details("info"),
+        )
+        .await;
+
+        Ok("OK")
+    }
+
+    /// Метод изменения настроек приватности полей профиля
+    #[graphql(guard = "AuthGuard::scope(Scope::ProfileWrite)")]
+    async fn edit_privacy_settings(
+        &'a self,
+        ctx: &'a Context<'_>,
+        input: PrivacySettingsInput,
+    ) -> GraphQLResult<&str> {
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let access_claims = get_access_claims(ctx);
+
+        profile_service
+            .set_privacy(access_claims.sub().to_string(), input.into())
+            .await?;
+
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::ProfileEdited)
+                .target(access_claims.sub())
+                .details("privacy"),
+        )
+        .await;
+
         Ok("OK")
     }
 }
@@ -170,21 +513,47 @@ pub struct ProfileQuery;
 
 #[Object]
 impl<'a> ProfileQuery {
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
+    /// Задача доказательства работы для регистрации.
+    ///
+    /// Сложность растет при всплеске регистраций
+    /// с IP адреса клиента или его подсети.
+    async fn registration_challenge(
+        &'a self,
+        ctx: &'a Context<'_>,
+    ) -> GraphQLResult<RegistrationChallenge> {
+        let ip = ctx.data_opt::<ClientInfo>().and_then(|client| client.ip());
+
+        Ok(ctx.data::<Arc<ProofOfWork>>()?.issue(ip)?)
+    }
+
+    /// Профиль, ожидающий удаления, виден только администраторам
+    #[graphql(
+        guard = "AuthGuard::scope(Scope::ProfileRead)",
+        complexity = "DB_COST + child_complexity"
+    )]
     async fn get_profile_data(
         &'a self,
         ctx: &'a Context<'_>,
         find_by: String,
     ) -> GraphQLResult<Profile> {
         let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
-        Ok(profile_service.get_data(find_by).await?)
+        let profile = profile_service.get_data(find_by).await?;
+
+        if profile.deletion_scheduled_at.is_some()
+            && !get_access_claims(ctx)
+                .permission()
+                .includes(Permission::Admin)
+        {
+            return Err(crate::not_found!("user").into());
+        }
+
+        Ok(profile)
     }
 
-    #[graphql(guard = "AuthGuard::new(Permission::Admin)
-        .or(AuthGuard::new(Permission::Developer))
-        .or(AuthGuard::new(Permission::User))")]
+    #[graphql(
+        guard = "AuthGuard::scope(Scope::ProfileRead)",
+        complexity = "DB_COST + LIST_SIZE * child_complexity"
+    )]
     async fn get_profile_native_langs(
         &'a self,
         ctx: &'a Context<'_>,
@@ -194,6 +563,7 @@ impl<'a> ProfileQuery {
         Ok(profile_service.get_native_langs(find_by).await?)
     }
 
+    #[graphql(complexity = "DB_COST + LIST_SIZE * child_complexity")]
     async fn get_profile_studied_langs(
         &'a self,
         ctx: &'a Context<'_>,
@@ -202,6 +572,83 @@ impl<'a> ProfileQuery {
         let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
         Ok(profile_service.get_studied_langs(find_by).await?)
     }
+
+    /// Поиск профилей по фильтрам.
+    ///
+    /// Профили упорядочены по имени пользователя, постраничный
+    /// вывод вперед по курсору `after`, не более `SEARCH_PAGE_MAX`
+    /// профилей на странице.
+    #[graphql(
+        guard = "AuthGuard::scope(Scope::ProfileRead)",
+        complexity = "DB_COST + page_size(first, SEARCH_PAGE_SIZE, SEARCH_PAGE_MAX) * child_complexity"
+    )]
+    async fn search_profiles(
+        &'a self,
+        ctx: &'a Context<'_>,
+        filter: Option<ProfileSearchFilter>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> GraphQLResult<Connection<String, Profile, SearchTotal>> {
+        let filter = filter.unwrap_or_default();
+        filter.validate().map_err(CustomError::from)?;
+
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let access_claims = get_access_claims(ctx);
+        let viewer_id = access_claims.sub().to_string();
+        let ignore_privacy = access_claims.permission().includes(Permission::Admin);
+        let limit = page_size(first, SEARCH_PAGE_SIZE, SEARCH_PAGE_MAX);
+
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<String>, _, _, _| async move {
+                let has_previous_page = after.is_some();
+
+                let (mut profiles, total_count) = profile_service
+                    .search(&filter, viewer_id, ignore_privacy, after, limit + 1)
+                    .await?;
+                let has_next_page = truncate_page(&mut profiles, limit);
+
+                let mut connection = Connection::with_additional_fields(
+                    has_previous_page,
+                    has_next_page,
+                    SearchTotal { total_count },
+                );
+                connection.edges.extend(
+                    profiles
+                        .into_iter()
+                        .map(|profile| Edge::new(profile.username.clone(), profile)),
+                );
+
+                Ok::<_, GraphQLError>(connection)
+            },
+        )
+        .await
+    }
+}
+
+/// Отправка письма со ссылкой подтверждения почты.
+///
+/// Токен привязан к текущему адресу профиля, поэтому
+/// после смены почты старые ссылки перестают действовать.
+async fn send_verification_email(ctx: &Context<'_>, profile: &Profile) -> GraphQLResult<()> {
+    let mailer = ctx.data::<Arc<dyn MailerT>>()?;
+
+    let token = Token::encode(ActionClaims::new(
+        profile.id.to_string(),
+        TokenPurpose::VerifyEmail,
+        Some(profile.email.clone()),
+        chrono::Duration::hours(VERIFY_EMAIL_HOURS),
+    ))
+    .map_err(|err| crate::internal!(&err.to_string()))?;
+
+    mailer
+        .send(templates::verify_email(&profile.email, &token))
+        .await?;
+
+    Ok(())
 }
 
 fn reg_validation<'a>(
@@ -247,13 +694,3 @@ fn reg_validation<'a>(
 
     Ok(())
 }
-
-/// Получение полезной нагрузки Access токена
-fn get_access_claims<'a>(ctx: &'a Context<'_>) -> &'a AccessClaims {
-    ctx.data_opt::<Result<Option<AccessClaims>, CustomError>>()
-        .unwrap()
-        .as_ref()
-        .unwrap()
-        .as_ref()
-        .unwrap()
-}
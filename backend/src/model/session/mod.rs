@@ -0,0 +1,5 @@
+pub mod session_model;
+pub mod session_resolver;
+pub mod session_repository;
+
+mod session_node;
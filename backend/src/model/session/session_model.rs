@@ -0,0 +1,43 @@
+use async_graphql::Object;
+use uuid::Uuid;
+
+/// Сессия профиля на отдельном устройстве.
+///
+/// Соответствует семейству refresh токенов, открытому при входе,
+/// и хранится узлом :Session, связанным с :Profile.
+pub struct Session {
+    pub(super) id: Uuid,
+    pub(super) ip: Option<String>,
+    pub(super) user_agent: Option<String>,
+    pub(super) created_at: i64,
+    pub(super) last_used_at: i64,
+    pub(super) current: bool,
+}
+
+#[Object]
+impl<'a> Session {
+    async fn id(&'a self) -> String {
+        self.id.to_string()
+    }
+
+    async fn ip(&'a self) -> &Option<String> {
+        &self.ip
+    }
+
+    async fn user_agent(&'a self) -> &Option<String> {
+        &self.user_agent
+    }
+
+    async fn created_at(&'a self) -> i64 {
+        self.created_at
+    }
+
+    async fn last_used_at(&'a self) -> i64 {
+        self.last_used_at
+    }
+
+    /// Сессия, в рамках которой выполнен запрос
+    async fn current(&'a self) -> bool {
+        self.current
+    }
+}
@@ -0,0 +1,20 @@
+use anyhow::Result;
+use neo4rs::Node;
+use uuid::Uuid;
+
+use crate::app::core::error::CustomError;
+
+use super::session_model::Session;
+
+impl<'a> Session {
+    pub(super) fn parse_query_resp(snode: Node) -> Result<Session, CustomError<'a>> {
+        Ok(Session {
+            id: Uuid::parse_str(&snode.get::<String>("id").unwrap())?,
+            ip: snode.get::<String>("ip"),
+            user_agent: snode.get::<String>("user_agent"),
+            created_at: snode.get::<i64>("created_at").unwrap(),
+            last_used_at: snode.get::<i64>("last_used_at").unwrap(),
+            current: false,
+        })
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use neo4rs::Graph;
+use std::sync::Arc;
+
+use crate::app::db::cypher;
+use crate::{app::core::error::CustomError, neo4j_result};
+
+use super::session_model::Session;
+
+#[async_trait]
+pub trait SessionRepositoryT: Send + Sync {
+    async fn delete(&self, profile_id: String, session_id: String) -> Result<bool, CustomError>;
+
+    async fn get_all(&self, profile_id: String) -> Result<Vec<Session>, CustomError>;
+}
+
+pub struct SessionRepository {
+    neo: Arc<Graph>,
+}
+
+impl SessionRepository {
+    pub fn new(neo4j: &Arc<Graph>) -> Self {
+        Self { neo: neo4j.clone() }
+    }
+}
+
+#[async_trait]
+impl SessionRepositoryT for SessionRepository {
+    /* ======================== MUTATIONS ======================== */
+
+    /// Удалить сессию профиля вместе с её refresh токенами.
+    ///
+    /// Возвращает `false`, если у профиля нет такой сессии.
+    async fn delete(&self, profile_id: String, session_id: String) -> Result<bool, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_SESSION]->(s:Session)
+                WHERE p.id = $profile_id AND s.id = $session_id
+                OPTIONAL MATCH (s)-[:ISSUED]->(t:RefreshToken)
+                DETACH DELETE t, s
+                RETURN count(*) AS deleted
+            ",
+        )
+        .param("profile_id", profile_id)
+        .param("session_id", session_id);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        if let Ok(Some(row)) = result.next().await {
+            return Ok(row.get::<i64>("deleted").unwrap_or(0) > 0);
+        }
+
+        Ok(false)
+    }
+
+    /* ======================== QUERYS ======================== */
+
+    /// Получить действующие сессии профиля
+    async fn get_all(&self, profile_id: String) -> Result<Vec<Session>, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_SESSION]->(s:Session)
+                WHERE p.id = $profile_id AND s.expires_at > $now
+                RETURN s
+                ORDER BY s.last_used_at DESC
+            ",
+        )
+        .param("profile_id", profile_id)
+        .param("now", Utc::now().timestamp());
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+        let mut output: Vec<Session> = Vec::new();
+
+        while let Ok(Some(row)) = result.next().await {
+            if let Some(snode) = row.get::<neo4rs::Node>("s") {
+                output.push(Session::parse_query_resp(snode)?);
+            }
+        }
+
+        Ok(output)
+    }
+}
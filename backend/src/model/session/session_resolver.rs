@@ -0,0 +1,77 @@
+use async_graphql::{Context, Object, Result as GraphQLResult};
+use std::sync::Arc;
+
+use crate::app::api::graphql::limits::{DB_COST, LIST_SIZE};
+use crate::app::api::security::{
+    auth::{get_access_claims, AuthGuard},
+    revocation::RevocationStoreT,
+};
+use crate::model::audit::audit_model::{audit, AuditEntry, AuditEvent};
+use crate::model::profile::profile_model::Permission;
+use crate::model::token::token_model::session_revocation_exp;
+
+use super::{session_model::Session, session_repository::SessionRepositoryT};
+
+#[derive(Default)]
+pub struct SessionMutation;
+
+#[Object]
+impl<'a> SessionMutation {
+    /// Метод завершения сессии на отдельном устройстве.
+    ///
+    /// Refresh токены сессии удаляются, а выпущенные в ней
+    /// Access токены отзываются.
+    #[graphql(guard = "AuthGuard::at_least(Permission::User).and(AuthGuard::session())")]
+    async fn revoke_session(&'a self, ctx: &'a Context<'_>, id: String) -> GraphQLResult<&str> {
+        let session_service = ctx.data::<Arc<dyn SessionRepositoryT>>()?;
+        let revocation_store = ctx.data::<Arc<dyn RevocationStoreT>>()?;
+        let access_claims = get_access_claims(ctx);
+
+        if !session_service
+            .delete(access_claims.sub().to_string(), id.clone())
+            .await?
+        {
+            return Err(crate::not_found!("session").into());
+        }
+
+        revocation_store
+            .revoke(id.clone(), session_revocation_exp())
+            .await?;
+
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::SessionRevoked)
+                .target(access_claims.sub())
+                .details(&id),
+        )
+        .await;
+
+        Ok("OK")
+    }
+}
+
+#[derive(Default)]
+pub struct SessionQuery;
+
+#[Object]
+impl<'a> SessionQuery {
+    /// Действующие сессии текущего профиля
+    #[graphql(
+        guard = "AuthGuard::at_least(Permission::User)",
+        complexity = "DB_COST + LIST_SIZE * child_complexity"
+    )]
+    async fn my_sessions(&'a self, ctx: &'a Context<'_>) -> GraphQLResult<Vec<Session>> {
+        let session_service = ctx.data::<Arc<dyn SessionRepositoryT>>()?;
+        let access_claims = get_access_claims(ctx);
+
+        let mut sessions = session_service
+            .get_all(access_claims.sub().to_string())
+            .await?;
+
+        for session in sessions.iter_mut() {
+            session.current = session.id.to_string() == access_claims.sid();
+        }
+
+        Ok(sessions)
+    }
+}
@@ -0,0 +1,6 @@
+pub mod suggestion_model;
+pub mod suggestion_repository;
+pub mod suggestion_resolver;
+
+mod suggestion_node;
+mod suggestion_rank;
@@ -0,0 +1,58 @@
+use async_graphql::{connection::CursorType, SimpleObject};
+
+use crate::model::profile::profile_model::Profile;
+
+/// Количество рекомендаций на странице по умолчанию
+pub(super) const SUGGESTIONS_PAGE_SIZE: usize = 20;
+/// Максимальное количество рекомендаций на странице
+pub(super) const SUGGESTIONS_PAGE_MAX: usize = 50;
+
+/// Рекомендованный для подписки профиль
+#[derive(SimpleObject)]
+pub struct FollowSuggestion {
+    pub(super) profile: Profile,
+    /// Оценка рекомендации от 0 до 1
+    pub(super) score: f64,
+    /// Количество моих подписок, подписанных на профиль
+    pub(super) mutual_follows: i64,
+    /// Коэффициент Жаккара наборов родных и изучаемых языков
+    pub(super) language_similarity: f64,
+    /// Популярность профиля от 0 до 1
+    pub(super) popularity: f64,
+}
+
+impl FollowSuggestion {
+    /// Курсор рекомендации для постраничного вывода
+    pub(super) fn cursor(&self) -> SuggestionCursor {
+        SuggestionCursor {
+            score: self.score,
+            id: self.profile.id.to_string(),
+        }
+    }
+}
+
+/// Курсор списка рекомендаций.
+///
+/// Рекомендации упорядочены по убыванию оценки,
+/// с одинаковой оценкой - по идентификатору профиля.
+pub struct SuggestionCursor {
+    pub(super) score: f64,
+    pub(super) id: String,
+}
+
+impl CursorType for SuggestionCursor {
+    type Error = &'static str;
+
+    fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+        let (score, id) = s.split_once(':').ok_or("Invalid suggestion cursor")?;
+
+        Ok(Self {
+            score: score.parse().map_err(|_| "Invalid suggestion cursor")?,
+            id: id.to_string(),
+        })
+    }
+
+    fn encode_cursor(&self) -> String {
+        format!("{}:{}", self.score, self.id)
+    }
+}
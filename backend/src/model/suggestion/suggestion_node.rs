@@ -0,0 +1,23 @@
+use anyhow::Result;
+use neo4rs::Row;
+
+use crate::app::core::error::CustomError;
+use crate::model::profile::profile_model::Profile;
+
+use super::suggestion_model::FollowSuggestion;
+
+impl<'a> FollowSuggestion {
+    pub(super) fn parse_row(row: &Row) -> Result<FollowSuggestion, CustomError<'a>> {
+        let pnode = row
+            .get::<neo4rs::Node>("c")
+            .ok_or_else(|| crate::internal!("Suggested profile node is missing"))?;
+
+        Ok(FollowSuggestion {
+            profile: Profile::parse_query_resp(pnode)?,
+            score: row.get::<f64>("score").unwrap_or(0.0),
+            mutual_follows: row.get::<i64>("mutual").unwrap_or(0),
+            language_similarity: row.get::<f64>("similarity").unwrap_or(0.0),
+            popularity: row.get::<f64>("popularity").unwrap_or(0.0),
+        })
+    }
+}
@@ -0,0 +1,86 @@
+/// Вероятность перехода по связи в модели PageRank
+const DAMPING: f64 = 0.85;
+/// Максимальное количество итераций
+const MAX_ITERATIONS: usize = 50;
+/// Суммарное изменение рангов, при котором расчет останавливается
+const TOLERANCE: f64 = 1e-6;
+
+/// Расчет PageRank по графу подписок.
+///
+/// `links[i]` содержит индексы профилей, на которые подписан профиль `i`.
+/// Ранг профилей без подписок распределяется поровну между всеми.
+/// Результат нормирован так, что самый популярный профиль получает 1.
+pub(super) fn page_rank(links: &[Vec<usize>]) -> Vec<f64> {
+    let count = links.len();
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let mut ranks = vec![1.0 / count as f64; count];
+
+    for _ in 0..MAX_ITERATIONS {
+        let dangling = links
+            .iter()
+            .zip(&ranks)
+            .filter(|(targets, _)| targets.is_empty())
+            .map(|(_, rank)| rank)
+            .sum::<f64>();
+
+        let base = (1.0 - DAMPING + DAMPING * dangling) / count as f64;
+        let mut next = vec![base; count];
+
+        for (source, targets) in links.iter().enumerate() {
+            let share = DAMPING * ranks[source] / targets.len() as f64;
+
+            for target in targets {
+                next[*target] += share;
+            }
+        }
+
+        let delta = next
+            .iter()
+            .zip(&ranks)
+            .map(|(next, prev)| (next - prev).abs())
+            .sum::<f64>();
+        ranks = next;
+
+        if delta < TOLERANCE {
+            break;
+        }
+    }
+
+    let max = ranks.iter().cloned().fold(0.0, f64::max);
+    ranks.iter().map(|rank| rank / max).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_graph() {
+        assert!(page_rank(&[]).is_empty());
+    }
+
+    #[test]
+    fn profiles_without_follows_rank_equally() {
+        assert_eq!(page_rank(&[vec![], vec![], vec![]]), vec![1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn cycle_ranks_equally() {
+        let ranks = page_rank(&[vec![1], vec![2], vec![0]]);
+
+        assert!(ranks.iter().all(|rank| (rank - 1.0).abs() < 1e-3));
+    }
+
+    #[test]
+    fn followed_profile_ranks_first() {
+        let ranks = page_rank(&[vec![], vec![0], vec![0], vec![0, 1]]);
+
+        assert_eq!(ranks[0], 1.0);
+        assert!(ranks[1] > ranks[2]);
+        assert!((ranks[2] - ranks[3]).abs() < 1e-9);
+        assert!(ranks.iter().all(|rank| *rank > 0.0 && *rank <= 1.0));
+    }
+}
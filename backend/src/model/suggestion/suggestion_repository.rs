@@ -0,0 +1,243 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use neo4rs::Graph;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::app::db::cypher;
+use crate::{app::core::error::CustomError, neo4j_result};
+
+use super::suggestion_model::{FollowSuggestion, SuggestionCursor};
+use super::suggestion_rank::page_rank;
+
+/// Количество профилей в одном запросе записи популярности
+const POPULARITY_BATCH: usize = 1000;
+/// Разделитель элементов списка
+const LIST_SEPARATOR: char = ';';
+/// Разделитель идентификатора профиля и значения
+const VALUE_SEPARATOR: char = '=';
+
+type EmptyResult<'a> = Result<(), CustomError<'a>>;
+
+#[async_trait]
+pub trait SuggestionRepositoryT: Send + Sync {
+    async fn dismiss(&self, profile_id: String, target_id: String) -> EmptyResult;
+    async fn update_popularity(&self) -> EmptyResult;
+
+    async fn suggest(
+        &self,
+        profile_id: String,
+        after: Option<SuggestionCursor>,
+        limit: usize,
+    ) -> Result<Vec<FollowSuggestion>, CustomError>;
+}
+
+pub struct SuggestionRepository {
+    neo: Arc<Graph>,
+}
+
+impl SuggestionRepository {
+    pub fn new(neo4j: &Arc<Graph>) -> Self {
+        Self { neo: neo4j.clone() }
+    }
+}
+
+#[async_trait]
+impl SuggestionRepositoryT for SuggestionRepository {
+    /* ======================== MUTATIONS ======================== */
+
+    /// Скрыть профиль из рекомендаций связью `:DISMISSED`
+    async fn dismiss(&self, profile_id: String, target_id: String) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (me:Profile) WHERE me.id = $id
+                MATCH (p:Profile) WHERE p.id = $target_id
+                MERGE (me)-[r:DISMISSED]->(p)
+                ON CREATE SET r.timestamp = $timestamp
+            ",
+        )
+        .param("id", profile_id)
+        .param("target_id", target_id)
+        .param("timestamp", Utc::now().timestamp());
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Пересчет популярности профилей по графу подписок.
+    ///
+    /// Граф загружается целиком, ранги считаются по PageRank
+    /// и записываются в свойство `popularity` узлов :Profile.
+    ///
+    /// Драйвер не поддерживает списки и дробные числа в параметрах,
+    /// поэтому списки передаются строкой через `LIST_SEPARATOR`
+    /// и `VALUE_SEPARATOR`, а ранги - их десятичной записью,
+    /// которая при разборе `toFloat` дает то же число.
+    /// Разделители передаются параметрами, чтобы запрос не расходился
+    /// с константами.
+    async fn update_popularity(&self) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)
+                RETURN p.id AS id,
+                    reduce(s = '', f IN [(p)-[:SUBSCRIBE]->(f:Profile) | f.id] | s + f + $list_sep)
+                        AS following
+            ",
+        )
+        .param("list_sep", LIST_SEPARATOR.to_string());
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+        let mut ids: Vec<String> = Vec::new();
+        let mut following: Vec<String> = Vec::new();
+
+        while let Ok(Some(row)) = result.next().await {
+            if let Some(id) = row.get::<String>("id") {
+                ids.push(id);
+                following.push(row.get::<String>("following").unwrap_or_default());
+            }
+        }
+
+        let index = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.as_str(), i))
+            .collect::<HashMap<_, _>>();
+
+        let links = following
+            .iter()
+            .map(|following| {
+                let mut targets = following
+                    .split(LIST_SEPARATOR)
+                    .filter_map(|id| index.get(id).copied())
+                    .collect::<Vec<_>>();
+                targets.sort_unstable();
+                targets.dedup();
+                targets
+            })
+            .collect::<Vec<_>>();
+
+        log::info!(
+            "Updating popularity: {} profiles, {} follows",
+            ids.len(),
+            links.iter().map(|targets| targets.len()).sum::<usize>()
+        );
+
+        let ranks = page_rank(&links);
+        let ranked = ids.iter().zip(&ranks).collect::<Vec<_>>();
+
+        for chunk in ranked.chunks(POPULARITY_BATCH) {
+            let scores = chunk
+                .iter()
+                .map(|(id, rank)| format!("{}{}{}", id, VALUE_SEPARATOR, rank))
+                .collect::<Vec<_>>()
+                .join(&LIST_SEPARATOR.to_string());
+
+            let query = cypher::query(
+                "
+                    UNWIND split($scores, $list_sep) AS item
+                    WITH split(item, $value_sep) AS pair
+                    MATCH (p:Profile) WHERE p.id = pair[0]
+                    SET p.popularity = toFloat(pair[1])
+                ",
+            )
+            .param("scores", scores)
+            .param("list_sep", LIST_SEPARATOR.to_string())
+            .param("value_sep", VALUE_SEPARATOR.to_string());
+
+            neo4j_result!(self.neo.run(query).await)?;
+        }
+
+        Ok(())
+    }
+
+    /* ======================== QUERYS ======================== */
+
+    /// Рекомендации подписок.
+    ///
+    /// Кандидаты - подписки моих подписок и профили с общими
+    /// языками, кроме уже моих подписок и скрытых мной профилей.
+    /// Оценка складывается из:
+    /// 1. подписок друзей (0.5) - доли моих подписок, подписанных на профиль;
+    /// 2. сходства языков (0.3) - коэффициента Жаккара наборов
+    ///    родных и изучаемых языков, родной и изучаемый язык различаются;
+    /// 3. популярности (0.2) - ранга PageRank в графе подписок.
+    ///
+    /// Страница выбирается после курсора `after` по оценке и идентификатору.
+    /// Оценка передается строкой, так как драйвер не поддерживает дробные
+    /// числа в параметрах.
+    async fn suggest(
+        &self,
+        profile_id: String,
+        after: Option<SuggestionCursor>,
+        limit: usize,
+    ) -> Result<Vec<FollowSuggestion>, CustomError> {
+        let (after_score, after_id) = after
+            .map(|after| (after.score.to_string(), after.id))
+            .unwrap_or_default();
+
+        let query = cypher::query(
+            "
+                MATCH (me:Profile) WHERE me.id = $id
+                WITH me,
+                    [(me)-[:NATIVE_SPEAKER]->(l:Language) | 'N:' + l.code]
+                        + [(me)-[:STUDIED]->(l:Language) | 'S:' + l.code] AS my_langs,
+                    size([(me)-[:SUBSCRIBE]->(f:Profile) | f]) AS my_following
+
+                CALL {
+                    WITH me
+                    MATCH (me)-[:SUBSCRIBE]->(:Profile)-[:SUBSCRIBE]->(c:Profile)
+                    RETURN c
+                    UNION
+                    WITH me
+                    MATCH (me)-[:NATIVE_SPEAKER|STUDIED]->(:Language)
+                        <-[:NATIVE_SPEAKER|STUDIED]-(c:Profile)
+                    RETURN c
+                }
+
+                WITH DISTINCT me, my_langs, my_following, c
+                WHERE c <> me AND NOT c:Suspended AND NOT c:Unverified AND NOT c:PendingDeletion
+                AND NOT exists((me)-[:SUBSCRIBE]->(c))
+                AND NOT exists((me)-[:DISMISSED]->(c))
+
+                WITH c, my_langs, my_following,
+                    size([(me)-[:SUBSCRIBE]->(f:Profile)-[:SUBSCRIBE]->(c) | f]) AS mutual,
+                    [(c)-[:NATIVE_SPEAKER]->(l:Language) | 'N:' + l.code]
+                        + [(c)-[:STUDIED]->(l:Language) | 'S:' + l.code] AS their_langs
+                WITH c, mutual, my_following,
+                    size([x IN my_langs WHERE x IN their_langs]) AS shared_langs,
+                    size(my_langs) + size(their_langs) AS total_langs
+
+                WITH c, mutual,
+                    CASE my_following WHEN 0 THEN 0.0
+                        ELSE toFloat(mutual) / my_following END AS social,
+                    CASE total_langs - shared_langs WHEN 0 THEN 0.0
+                        ELSE toFloat(shared_langs) / (total_langs - shared_langs) END AS similarity,
+                    coalesce(c.popularity, 0.0) AS popularity
+
+                WITH c, mutual, similarity, popularity,
+                    0.5 * social + 0.3 * similarity + 0.2 * popularity AS score
+                WHERE $after_id = ''
+                    OR score < toFloat($after_score)
+                    OR (score = toFloat($after_score) AND c.id > $after_id)
+
+                RETURN c, mutual, similarity, popularity, score
+                ORDER BY score DESC, c.id
+                LIMIT $limit
+            ",
+        )
+        .param("id", profile_id)
+        .param("after_score", after_score)
+        .param("after_id", after_id)
+        .param("limit", limit as i64);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+        let mut output: Vec<FollowSuggestion> = Vec::new();
+
+        while let Ok(Some(row)) = result.next().await {
+            output.push(FollowSuggestion::parse_row(&row)?);
+        }
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,86 @@
+use async_graphql::connection::{query, Connection, Edge};
+use async_graphql::{Context, Error as GraphQLError, Object, Result as GraphQLResult};
+use std::sync::Arc;
+
+use crate::app::api::graphql::limits::{page_size, truncate_page, DB_COST};
+use crate::app::api::security::auth::{get_access_claims, AuthGuard};
+use crate::app::api::security::scope::Scope;
+
+use super::{
+    suggestion_model::{
+        FollowSuggestion, SuggestionCursor, SUGGESTIONS_PAGE_MAX, SUGGESTIONS_PAGE_SIZE,
+    },
+    suggestion_repository::SuggestionRepositoryT,
+};
+
+#[derive(Default)]
+pub struct SuggestionMutation;
+
+#[Object]
+impl<'a> SuggestionMutation {
+    /// Скрыть профиль из рекомендаций подписок
+    #[graphql(guard = "AuthGuard::scope(Scope::ProfileWrite)")]
+    async fn dismiss_suggestion(
+        &'a self,
+        ctx: &'a Context<'_>,
+        profile_id: String,
+    ) -> GraphQLResult<&str> {
+        let suggestion_service = ctx.data::<Arc<dyn SuggestionRepositoryT>>()?;
+        let access_claims = get_access_claims(ctx);
+
+        suggestion_service
+            .dismiss(access_claims.sub().to_string(), profile_id)
+            .await?;
+
+        Ok("OK")
+    }
+}
+
+#[derive(Default)]
+pub struct SuggestionQuery;
+
+#[Object]
+impl<'a> SuggestionQuery {
+    /// Рекомендации подписок по графу подписок, сходству языков
+    /// и популярности профилей, от лучшей к худшей.
+    /// Скрытые через `dismissSuggestion` профили не рекомендуются.
+    #[graphql(
+        guard = "AuthGuard::scope(Scope::ProfileRead)",
+        complexity = "DB_COST + page_size(first, SUGGESTIONS_PAGE_SIZE, SUGGESTIONS_PAGE_MAX) * child_complexity"
+    )]
+    async fn suggested_follows(
+        &'a self,
+        ctx: &'a Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> GraphQLResult<Connection<SuggestionCursor, FollowSuggestion>> {
+        let suggestion_service = ctx.data::<Arc<dyn SuggestionRepositoryT>>()?;
+        let profile_id = get_access_claims(ctx).sub().to_string();
+        let limit = page_size(first, SUGGESTIONS_PAGE_SIZE, SUGGESTIONS_PAGE_MAX);
+
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<SuggestionCursor>, _, _, _| async move {
+                let has_previous_page = after.is_some();
+
+                let mut suggestions = suggestion_service
+                    .suggest(profile_id, after, limit + 1)
+                    .await?;
+                let has_next_page = truncate_page(&mut suggestions, limit);
+
+                let mut connection = Connection::new(has_previous_page, has_next_page);
+                connection.edges.extend(
+                    suggestions
+                        .into_iter()
+                        .map(|suggestion| Edge::new(suggestion.cursor(), suggestion)),
+                );
+
+                Ok::<_, GraphQLError>(connection)
+            },
+        )
+        .await
+    }
+}
@@ -0,0 +1,6 @@
+pub mod token_model;
+pub mod token_resolver;
+pub mod token_repository;
+pub mod token_error;
+
+mod token_node;
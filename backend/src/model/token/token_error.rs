@@ -0,0 +1,6 @@
+lazy_static! {
+    pub static ref ERR_TOKEN__REUSED: &'static str =
+        "Refresh token has already been used, all related tokens have been revoked";
+    pub static ref ERR_TOKEN__UNKNOWN: &'static str =
+        "Refresh token was not issued or has been revoked";
+}
@@ -0,0 +1,219 @@
+use async_graphql::{Context, Result as GraphQLResult};
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::app::api::security::{
+    auth::{AccessClaims, ActionClaims, RefreshClaims, Token, TokenPurpose},
+    client::ClientInfo,
+    lockout::LoginLockout,
+    revocation::RevocationStoreT,
+};
+use crate::app::core::error::{CustomError, CustomErrorKind::Forbidden};
+use crate::model::audit::audit_model::{audit, AuditEntry, AuditEvent};
+use crate::model::profile::{
+    profile_error::ERR_PROF__DELETION_BY_ADMIN,
+    profile_model::Profile,
+    profile_mutation::{LoginResult, ProfileLoginOutput},
+    profile_repository::ProfileRepositoryT,
+};
+use crate::model::two_factor::{
+    two_factor_model::{TwoFactorChallenge, CHALLENGE_MINUTES},
+    two_factor_repository::TwoFactorRepositoryT,
+};
+
+use super::token_repository::TokenRepositoryT;
+
+/// Срок действия Access токена в минутах
+pub(crate) const ACCESS_TOKEN_MINUTES: i64 = 15;
+/// Срок действия refresh токена в днях
+pub(crate) const REFRESH_TOKEN_DAYS: i64 = 7;
+
+/// Refresh токен, сохраненный в графе.
+///
+/// Каждый токен одноразовый: после обмена на новую пару
+/// он помечается как использованный, но не удаляется,
+/// чтобы повторное предъявление можно было обнаружить.
+pub struct RefreshToken {
+    pub(super) family: Uuid,
+    pub(super) profile_id: Uuid,
+    pub(super) used: bool,
+}
+
+/// Выпуск новой пары токенов для профиля.
+///
+/// Refresh токен сохраняется в графе в рамках указанного семейства.
+/// Для нового входа передается новый идентификатор семейства,
+/// при ротации - семейство предъявленного токена. Идентификатор
+/// семейства является идентификатором сессии устройства.
+///
+/// Заблокированному профилю токены не выдаются. Вход профиля,
+/// ожидающего удаления по собственному запросу, отменяет удаление,
+/// профилю, удаляемому администратором, токены не выдаются.
+pub(crate) async fn issue_token_pair(
+    ctx: &Context<'_>,
+    profile: &Profile,
+    family: Uuid,
+) -> GraphQLResult<ProfileLoginOutput> {
+    if let Some(suspension) = &profile.suspension {
+        return Err(suspension.error().into());
+    }
+
+    if profile.deletion_scheduled_at.is_some() {
+        if profile.deletion_requested_by.as_deref() != Some(&profile.id.to_string()) {
+            return Err(CustomError::new()
+                .kind(Forbidden)
+                .details(&ERR_PROF__DELETION_BY_ADMIN)
+                .build()
+                .into());
+        }
+
+        cancel_deletion(ctx, profile).await?;
+    }
+
+    let token_service = ctx.data::<Arc<dyn TokenRepositoryT>>()?;
+    let revocation_store = ctx.data::<Arc<dyn RevocationStoreT>>()?;
+    let client = ctx.data_opt::<ClientInfo>().cloned().unwrap_or_default();
+
+    let generation = revocation_store.generation(profile.id.to_string()).await?;
+    let refresh_claims = RefreshClaims::new(
+        profile.id.to_string(),
+        family.to_string(),
+        Duration::days(REFRESH_TOKEN_DAYS),
+    );
+
+    token_service
+        .create(profile.id.to_string(), &refresh_claims, &client)
+        .await?;
+
+    let access_token = Token::encode(AccessClaims::new(
+        profile.id.to_string(),
+        profile.permission,
+        family.to_string(),
+        generation,
+        Duration::minutes(ACCESS_TOKEN_MINUTES),
+    ))
+    .map_err(|err| crate::internal!(&err.to_string()))?;
+    let refresh_token =
+        Token::encode(refresh_claims).map_err(|err| crate::internal!(&err.to_string()))?;
+
+    Ok(ProfileLoginOutput::create(access_token, refresh_token))
+}
+
+/// Завершение входа профиля, подтвердившего первый фактор.
+///
+/// При подключенной двухфакторной аутентификации вместо токенов
+/// возвращается токен второго шага для `loginSecondFactor`.
+pub(crate) async fn complete_login(
+    ctx: &Context<'_>,
+    profile: &Profile,
+) -> GraphQLResult<LoginResult> {
+    let two_factor_service = ctx.data::<Arc<dyn TwoFactorRepositoryT>>()?;
+
+    if let Some(two_factor) = two_factor_service.get(profile.id.to_string()).await? {
+        if two_factor.enabled() {
+            // Токен привязан к паролю, чтобы его смена
+            // прерывала незавершенный вход
+            let challenge_token = Token::encode(ActionClaims::new(
+                profile.id.to_string(),
+                TokenPurpose::TwoFactorLogin,
+                Some(profile.password_fingerprint()),
+                Duration::minutes(CHALLENGE_MINUTES),
+            ))
+            .map_err(|err| crate::internal!(&err.to_string()))?;
+
+            return Ok(LoginResult::Challenge(TwoFactorChallenge::new(
+                challenge_token,
+            )));
+        }
+    }
+
+    // Каждый вход открывает новое семейство refresh токенов
+    let tokens = issue_token_pair(ctx, profile, Uuid::new_v4()).await?;
+    audit_login(ctx, profile).await;
+
+    Ok(LoginResult::Tokens(tokens))
+}
+
+/// Проверка блокировки входа по IP адресу и, если он известен, по профилю.
+/// Отклоненная попытка записывается в журнал аудита.
+pub(crate) async fn check_lockout(
+    ctx: &Context<'_>,
+    profile_id: Option<&str>,
+) -> GraphQLResult<()> {
+    let lockout = ctx.data::<Arc<LoginLockout>>()?;
+    let ip = ctx.data_opt::<ClientInfo>().and_then(|client| client.ip());
+
+    let result = lockout.check_ip(ip).and_then(|_| match profile_id {
+        Some(profile_id) => lockout.check_account(profile_id),
+        None => Ok(()),
+    });
+
+    if let Err(err) = result {
+        let mut entry = AuditEntry::new(AuditEvent::LoginLocked);
+        if let Some(profile_id) = profile_id {
+            entry = entry.target(profile_id);
+        }
+
+        audit(ctx, entry).await;
+        return Err(err.into());
+    }
+
+    Ok(())
+}
+
+/// Отмена удаления профиля при входе
+async fn cancel_deletion(ctx: &Context<'_>, profile: &Profile) -> GraphQLResult<()> {
+    let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+    let profile_id = profile.id.to_string();
+
+    profile_service.cancel_deletion(profile_id.clone()).await?;
+
+    audit(
+        ctx,
+        AuditEntry::new(AuditEvent::ProfileDeletionCancelled)
+            .actor(&profile_id)
+            .target(&profile_id),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Запись успешного входа в журнал аудита
+pub(crate) async fn audit_login(ctx: &Context<'_>, profile: &Profile) {
+    let profile_id = profile.id.to_string();
+
+    audit(
+        ctx,
+        AuditEntry::new(AuditEvent::LoginSucceeded)
+            .actor(&profile_id)
+            .target(&profile_id),
+    )
+    .await;
+}
+
+/// Срок хранения записи об отзыве сессии.
+///
+/// Запись должна пережить любой Access токен, выпущенный
+/// в рамках сессии до её отзыва.
+pub(crate) fn session_revocation_exp() -> i64 {
+    (Utc::now() + Duration::minutes(ACCESS_TOKEN_MINUTES)).timestamp()
+}
+
+/// Завершение всех сессий профиля.
+///
+/// Выпущенные Access токены отзываются сменой поколения,
+/// refresh токены всех семейств удаляются из графа.
+pub(crate) async fn revoke_all_sessions(
+    ctx: &Context<'_>,
+    profile_id: String,
+) -> GraphQLResult<()> {
+    let token_service = ctx.data::<Arc<dyn TokenRepositoryT>>()?;
+    let revocation_store = ctx.data::<Arc<dyn RevocationStoreT>>()?;
+
+    revocation_store.revoke_all(profile_id.clone()).await?;
+    token_service.revoke_all(profile_id).await?;
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use neo4rs::Node;
+use uuid::Uuid;
+
+use crate::app::core::error::CustomError;
+
+use super::token_model::RefreshToken;
+
+impl<'a> RefreshToken {
+    /// Разбор узла :RefreshToken
+    pub(super) fn parse_query_resp(
+        tnode: Node,
+        profile_id: String,
+    ) -> Result<RefreshToken, CustomError<'a>> {
+        Ok(RefreshToken {
+            family: Uuid::parse_str(&tnode.get::<String>("family").unwrap())?,
+            profile_id: Uuid::parse_str(&profile_id)?,
+            used: tnode.get::<bool>("used").unwrap_or(true),
+        })
+    }
+}
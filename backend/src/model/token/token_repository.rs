@@ -0,0 +1,168 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use neo4rs::Graph;
+use std::sync::Arc;
+
+use crate::app::api::security::{auth::RefreshClaims, client::ClientInfo};
+use crate::app::db::cypher;
+use crate::{app::core::error::CustomError, neo4j_result};
+
+use super::token_model::RefreshToken;
+
+type EmptyResult<'a> = Result<(), CustomError<'a>>;
+
+#[async_trait]
+pub trait TokenRepositoryT: Send + Sync {
+    async fn create(
+        &self,
+        profile_id: String,
+        claims: &RefreshClaims,
+        client: &ClientInfo,
+    ) -> EmptyResult;
+    async fn mark_used(&self, jti: String) -> Result<bool, CustomError>;
+    async fn revoke_family(&self, family: String) -> EmptyResult;
+    async fn revoke_all(&self, profile_id: String) -> EmptyResult;
+    async fn get(&self, jti: String) -> Result<Option<RefreshToken>, CustomError>;
+}
+
+pub struct TokenRepository {
+    neo: Arc<Graph>,
+}
+
+impl TokenRepository {
+    pub fn new(neo4j: &Arc<Graph>) -> Self {
+        Self { neo: neo4j.clone() }
+    }
+}
+
+#[async_trait]
+impl TokenRepositoryT for TokenRepository {
+    /* ======================== MUTATIONS ======================== */
+
+    /// Сохранить выпущенный refresh токен.
+    ///
+    /// Семейство токенов соответствует сессии :Session профиля.
+    /// Сессия создается при первом токене семейства, при каждой
+    /// ротации обновляются сведения о клиенте и время использования.
+    async fn create(
+        &self,
+        profile_id: String,
+        claims: &RefreshClaims,
+        client: &ClientInfo,
+    ) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile) WHERE p.id = $profile_id
+                MERGE (p)-[:HAS_SESSION]->(s:Session {id: $family})
+                ON CREATE SET s.created_at = $timestamp
+                SET s.last_used_at = $timestamp,
+                    s.expires_at = $exp,
+                    s.ip = CASE $ip WHEN '' THEN null ELSE $ip END,
+                    s.user_agent = CASE $user_agent WHEN '' THEN null ELSE $user_agent END
+                CREATE (s)-[:ISSUED]->(t:RefreshToken {
+                    jti: $jti,
+                    family: $family,
+                    used: false,
+                    exp: $exp,
+                    created_at: $timestamp
+                })
+            ",
+        )
+        .param("profile_id", profile_id)
+        .param("jti", claims.jti().to_string())
+        .param("family", claims.family().to_string())
+        .param("exp", claims.exp())
+        .param("ip", client.ip().unwrap_or_default().to_string())
+        .param(
+            "user_agent",
+            client.user_agent().unwrap_or_default().to_string(),
+        )
+        .param("timestamp", Utc::now().timestamp());
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Пометить токен использованным.
+    ///
+    /// Условие и изменение выполняются одним запросом, поэтому из
+    /// параллельных запросов с одним токеном успешен только один.
+    /// Возвращает `false`, если токен уже был использован.
+    async fn mark_used(&self, jti: String) -> Result<bool, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (t:RefreshToken)
+                WHERE t.jti = $jti AND t.used = false
+                SET t.used = true, t.used_at = $timestamp
+                RETURN count(t) AS marked
+            ",
+        )
+        .param("jti", jti)
+        .param("timestamp", Utc::now().timestamp());
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        match result.next().await {
+            Ok(Some(row)) => Ok(row.get::<i64>("marked").unwrap_or_default() > 0),
+            _ => Ok(false),
+        }
+    }
+
+    /// Удалить сессию вместе со всеми токенами семейства
+    async fn revoke_family(&self, family: String) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (s:Session) WHERE s.id = $family
+                OPTIONAL MATCH (s)-[:ISSUED]->(t:RefreshToken)
+                DETACH DELETE t, s
+            ",
+        )
+        .param("family", family);
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Удалить все сессии и refresh токены профиля
+    async fn revoke_all(&self, profile_id: String) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_SESSION]->(s:Session) WHERE p.id = $profile_id
+                OPTIONAL MATCH (s)-[:ISSUED]->(t:RefreshToken)
+                DETACH DELETE t, s
+            ",
+        )
+        .param("profile_id", profile_id);
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /* ======================== QUERYS ======================== */
+
+    /// Получить refresh токен вместе с профилем-владельцем
+    async fn get(&self, jti: String) -> Result<Option<RefreshToken>, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_SESSION]->(:Session)-[:ISSUED]->(t:RefreshToken)
+                WHERE t.jti = $jti
+                RETURN t, p.id AS profile_id
+            ",
+        )
+        .param("jti", jti);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        if let Ok(Some(row)) = result.next().await {
+            if let (Some(tnode), Some(profile_id)) = (
+                row.get::<neo4rs::Node>("t"),
+                row.get::<String>("profile_id"),
+            ) {
+                return Ok(Some(RefreshToken::parse_query_resp(tnode, profile_id)?));
+            }
+        }
+
+        Ok(None)
+    }
+}
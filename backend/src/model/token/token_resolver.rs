@@ -0,0 +1,112 @@
+use async_graphql::{Context, Object, Result as GraphQLResult};
+use std::sync::Arc;
+
+use crate::app::api::security::{
+    auth::{get_access_claims, AuthGuard, RefreshClaims, Token},
+    revocation::RevocationStoreT,
+};
+use crate::app::core::error::{CustomError, CustomErrorKind::TokenInvalid};
+use crate::model::audit::audit_model::{audit, AuditEntry, AuditEvent};
+use crate::model::profile::{
+    profile_model::Permission, profile_mutation::ProfileLoginOutput,
+    profile_repository::ProfileRepositoryT,
+};
+
+use super::{
+    token_error::{ERR_TOKEN__REUSED, ERR_TOKEN__UNKNOWN},
+    token_model::{issue_token_pair, revoke_all_sessions, session_revocation_exp},
+    token_repository::TokenRepositoryT,
+};
+
+#[derive(Default)]
+pub struct TokenMutation;
+
+#[Object]
+impl<'a> TokenMutation {
+    /// Метод обновления пары токенов.
+    ///
+    /// Refresh токен одноразовый: в ответ клиент получает новую пару,
+    /// а предъявленный токен помечается использованным. Повторное
+    /// предъявление использованного токена отзывает всё его семейство.
+    async fn refresh_tokens(
+        &'a self,
+        ctx: &'a Context<'_>,
+        refresh_token: String,
+    ) -> GraphQLResult<ProfileLoginOutput> {
+        let claims = Token::<RefreshClaims>::decode(&refresh_token).map_err(CustomError::from)?;
+        let token_service = ctx.data::<Arc<dyn TokenRepositoryT>>()?;
+
+        let token = match token_service.get(claims.jti().to_string()).await? {
+            Some(token) if token.profile_id.to_string() == claims.sub() => token,
+            _ => {
+                return Err(CustomError::new()
+                    .kind(TokenInvalid)
+                    .details(&ERR_TOKEN__UNKNOWN)
+                    .build()
+                    .into())
+            }
+        };
+
+        if token.used || !token_service.mark_used(claims.jti().to_string()).await? {
+            token_service
+                .revoke_family(token.family.to_string())
+                .await?;
+
+            return Err(CustomError::new()
+                .kind(TokenInvalid)
+                .details(&ERR_TOKEN__REUSED)
+                .build()
+                .into());
+        }
+
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let profile = profile_service
+            .get_data(token.profile_id.to_string())
+            .await?;
+
+        let tokens = issue_token_pair(ctx, &profile, token.family).await?;
+
+        let profile_id = profile.id.to_string();
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::TokenRefreshed)
+                .actor(&profile_id)
+                .target(&profile_id),
+        )
+        .await;
+
+        Ok(tokens)
+    }
+
+    /// Метод выхода из текущей сессии.
+    ///
+    /// Удаляет сессию запроса вместе с её refresh токенами
+    /// и отзывает выпущенные в ней Access токены.
+    #[graphql(guard = "AuthGuard::at_least(Permission::User).and(AuthGuard::session())")]
+    async fn logout(&'a self, ctx: &'a Context<'_>) -> GraphQLResult<&str> {
+        let token_service = ctx.data::<Arc<dyn TokenRepositoryT>>()?;
+        let revocation_store = ctx.data::<Arc<dyn RevocationStoreT>>()?;
+        let access_claims = get_access_claims(ctx);
+
+        token_service
+            .revoke_family(access_claims.sid().to_string())
+            .await?;
+        revocation_store
+            .revoke(access_claims.sid().to_string(), session_revocation_exp())
+            .await?;
+
+        Ok("OK")
+    }
+
+    /// Метод выхода из всех сессий профиля.
+    ///
+    /// Отзывает все выпущенные ранее Access и refresh токены.
+    #[graphql(guard = "AuthGuard::at_least(Permission::User).and(AuthGuard::session())")]
+    async fn logout_all_sessions(&'a self, ctx: &'a Context<'_>) -> GraphQLResult<&str> {
+        let access_claims = get_access_claims(ctx);
+
+        revoke_all_sessions(ctx, access_claims.sub().to_string()).await?;
+
+        Ok("OK")
+    }
+}
@@ -0,0 +1,6 @@
+pub mod two_factor_error;
+pub mod two_factor_model;
+pub mod two_factor_repository;
+pub mod two_factor_resolver;
+
+mod two_factor_node;
@@ -0,0 +1,8 @@
+lazy_static! {
+    pub static ref ERR_2FA__ALREADY_ENABLED: &'static str =
+        "Two-factor authentication is already enabled";
+    pub static ref ERR_2FA__NOT_ENROLLED: &'static str =
+        "Two-factor authentication is not enrolled";
+    pub static ref ERR_2FA__NOT_ENABLED: &'static str = "Two-factor authentication is not enabled";
+    pub static ref ERR_2FA__INVALID_CODE: &'static str = "Invalid two-factor authentication code";
+}
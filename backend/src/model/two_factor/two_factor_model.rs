@@ -0,0 +1,90 @@
+use async_graphql::{Object, SimpleObject};
+use rand::Rng;
+use uuid::Uuid;
+
+/// Количество кодов восстановления, выдаваемых при подключении
+pub(crate) const RECOVERY_CODES_COUNT: usize = 10;
+/// Срок действия токена второго шага входа в минутах
+pub(crate) const CHALLENGE_MINUTES: i64 = 5;
+
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghijkmnpqrstuvwxyz23456789";
+
+/// Настройки двухфакторной аутентификации профиля.
+///
+/// Хранятся узлом :TwoFactor, связанным с :Profile. До подтверждения
+/// первым кодом секрет считается неактивным и на вход не влияет.
+pub struct TwoFactor {
+    pub(super) secret: String,
+    pub(super) enabled: bool,
+    /// Последний принятый шаг TOTP
+    pub(super) last_step: i64,
+}
+
+impl TwoFactor {
+    pub(crate) fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+/// Код восстановления, хранится argon2 хешем узлом :RecoveryCode
+pub struct RecoveryCode {
+    pub(super) id: Uuid,
+    pub(super) hash: String,
+}
+
+/// Генерация кода восстановления вида `xxxxx-xxxxx`
+pub(super) fn generate_recovery_code() -> String {
+    let mut rng = rand::thread_rng();
+    let mut code: String = (0..10)
+        .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+        .collect();
+
+    code.insert(5, '-');
+    code
+}
+
+/// Приведение введенного кода восстановления к виду, в котором он хешируется
+pub(super) fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+#[derive(SimpleObject)]
+pub struct TwoFactorEnrollment {
+    /// Секрет в кодировке Base32 для ручного ввода
+    secret: String,
+    /// Ссылка `otpauth://` для QR-кода
+    otpauth_uri: String,
+}
+
+impl TwoFactorEnrollment {
+    pub(super) fn new(secret: String, otpauth_uri: String) -> Self {
+        Self {
+            secret,
+            otpauth_uri,
+        }
+    }
+}
+
+/// Ответ на вход с подключенной двухфакторной аутентификацией.
+///
+/// Токен предъявляется в `loginSecondFactor` вместе с кодом.
+#[derive(Serialize, Deserialize)]
+pub struct TwoFactorChallenge {
+    challenge_token: String,
+}
+
+impl TwoFactorChallenge {
+    pub(crate) fn new(challenge_token: String) -> Self {
+        Self { challenge_token }
+    }
+}
+
+#[Object]
+impl<'a> TwoFactorChallenge {
+    async fn challenge_token(&'a self) -> &str {
+        &self.challenge_token
+    }
+}
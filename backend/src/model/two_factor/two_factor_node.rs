@@ -0,0 +1,26 @@
+use anyhow::Result;
+use neo4rs::Node;
+use uuid::Uuid;
+
+use crate::app::core::error::CustomError;
+
+use super::two_factor_model::{RecoveryCode, TwoFactor};
+
+impl TwoFactor {
+    pub(super) fn parse_query_resp(tnode: Node) -> TwoFactor {
+        TwoFactor {
+            secret: tnode.get::<String>("secret").unwrap(),
+            enabled: tnode.get::<bool>("enabled").unwrap_or(false),
+            last_step: tnode.get::<i64>("last_step").unwrap_or(0),
+        }
+    }
+}
+
+impl<'a> RecoveryCode {
+    pub(super) fn parse_query_resp(cnode: Node) -> Result<RecoveryCode, CustomError<'a>> {
+        Ok(RecoveryCode {
+            id: Uuid::parse_str(&cnode.get::<String>("id").unwrap())?,
+            hash: cnode.get::<String>("hash").unwrap(),
+        })
+    }
+}
@@ -0,0 +1,185 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use neo4rs::Graph;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::app::db::cypher;
+use crate::{app::core::error::CustomError, neo4j_result};
+
+use super::two_factor_model::{RecoveryCode, TwoFactor};
+
+type EmptyResult<'a> = Result<(), CustomError<'a>>;
+
+#[async_trait]
+pub trait TwoFactorRepositoryT: Send + Sync {
+    async fn enroll(&self, profile_id: String, secret: String) -> EmptyResult;
+    async fn enable(&self, profile_id: String, recovery_hashes: Vec<String>) -> EmptyResult;
+    async fn disable(&self, profile_id: String) -> EmptyResult;
+    async fn set_last_step(&self, profile_id: String, step: i64) -> EmptyResult;
+    async fn use_recovery_code(&self, id: Uuid) -> EmptyResult;
+
+    async fn get(&self, profile_id: String) -> Result<Option<TwoFactor>, CustomError>;
+    async fn get_recovery_codes(
+        &self,
+        profile_id: String,
+    ) -> Result<Vec<RecoveryCode>, CustomError>;
+}
+
+pub struct TwoFactorRepository {
+    neo: Arc<Graph>,
+}
+
+impl TwoFactorRepository {
+    pub fn new(neo4j: &Arc<Graph>) -> Self {
+        Self { neo: neo4j.clone() }
+    }
+}
+
+#[async_trait]
+impl TwoFactorRepositoryT for TwoFactorRepository {
+    /* ======================== MUTATIONS ======================== */
+
+    /// Сохранить новый неподтвержденный секрет.
+    /// Ранее созданный неподтвержденный секрет заменяется.
+    async fn enroll(&self, profile_id: String, secret: String) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile) WHERE p.id = $profile_id
+                MERGE (p)-[:HAS_TWO_FACTOR]->(t:TwoFactor)
+                SET t.secret = $secret,
+                    t.enabled = false,
+                    t.last_step = 0,
+                    t.created_at = $timestamp
+            ",
+        )
+        .param("profile_id", profile_id)
+        .param("secret", secret)
+        .param("timestamp", Utc::now().timestamp());
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Включить двухфакторную аутентификацию.
+    /// Прежние коды восстановления заменяются новыми.
+    async fn enable(&self, profile_id: String, recovery_hashes: Vec<String>) -> EmptyResult {
+        let enable = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_TWO_FACTOR]->(t:TwoFactor) WHERE p.id = $profile_id
+                SET t.enabled = true
+                WITH t
+                OPTIONAL MATCH (t)-[:HAS_RECOVERY_CODE]->(c:RecoveryCode)
+                DETACH DELETE c
+            ",
+        )
+        .param("profile_id", profile_id.clone());
+
+        let codes = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_TWO_FACTOR]->(t:TwoFactor) WHERE p.id = $profile_id
+                UNWIND $hashes AS hash
+                CREATE (t)-[:HAS_RECOVERY_CODE]->(:RecoveryCode {id: randomUUID(), hash: hash})
+            ",
+        )
+        .param("profile_id", profile_id)
+        .param("hashes", recovery_hashes);
+
+        let txn = self.neo.start_txn().await?;
+        neo4j_result!(txn.run_queries(vec![enable, codes]).await)?;
+
+        Ok(neo4j_result!(txn.commit().await)?)
+    }
+
+    /// Отключить двухфакторную аутентификацию вместе с кодами восстановления
+    async fn disable(&self, profile_id: String) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_TWO_FACTOR]->(t:TwoFactor) WHERE p.id = $profile_id
+                OPTIONAL MATCH (t)-[:HAS_RECOVERY_CODE]->(c:RecoveryCode)
+                DETACH DELETE c, t
+            ",
+        )
+        .param("profile_id", profile_id);
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Запомнить последний принятый шаг TOTP
+    async fn set_last_step(&self, profile_id: String, step: i64) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_TWO_FACTOR]->(t:TwoFactor) WHERE p.id = $profile_id
+                SET t.last_step = $step
+            ",
+        )
+        .param("profile_id", profile_id)
+        .param("step", step);
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /// Удалить использованный код восстановления
+    async fn use_recovery_code(&self, id: Uuid) -> EmptyResult {
+        let query = cypher::query(
+            "
+                MATCH (c:RecoveryCode) WHERE c.id = $id
+                DETACH DELETE c
+            ",
+        )
+        .param("id", id.to_string());
+
+        neo4j_result!(self.neo.run(query).await)?;
+        Ok(())
+    }
+
+    /* ======================== QUERYS ======================== */
+
+    async fn get(&self, profile_id: String) -> Result<Option<TwoFactor>, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_TWO_FACTOR]->(t:TwoFactor) WHERE p.id = $profile_id
+                RETURN t
+            ",
+        )
+        .param("profile_id", profile_id);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+
+        if let Ok(Some(row)) = result.next().await {
+            if let Some(tnode) = row.get::<neo4rs::Node>("t") {
+                return Ok(Some(TwoFactor::parse_query_resp(tnode)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn get_recovery_codes(
+        &self,
+        profile_id: String,
+    ) -> Result<Vec<RecoveryCode>, CustomError> {
+        let query = cypher::query(
+            "
+                MATCH (p:Profile)-[:HAS_TWO_FACTOR]->(:TwoFactor)-[:HAS_RECOVERY_CODE]->(c:RecoveryCode)
+                WHERE p.id = $profile_id
+                RETURN c
+            ",
+        )
+        .param("profile_id", profile_id);
+
+        let mut result = neo4j_result!(self.neo.execute(query).await)?;
+        let mut output: Vec<RecoveryCode> = Vec::new();
+
+        while let Ok(Some(row)) = result.next().await {
+            if let Some(cnode) = row.get::<neo4rs::Node>("c") {
+                output.push(RecoveryCode::parse_query_resp(cnode)?);
+            }
+        }
+
+        Ok(output)
+    }
+}
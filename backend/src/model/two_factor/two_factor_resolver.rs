@@ -0,0 +1,291 @@
+use async_graphql::{Context, Object, Result as GraphQLResult};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::app::api::security::{
+    auth::{get_access_claims, ActionClaims, AuthGuard, TokenPurpose},
+    client::ClientInfo,
+    lockout::LoginLockout,
+    totp,
+};
+use crate::app::core::error::{
+    CustomError,
+    CustomErrorKind::{InvalidCredentials, TokenInvalid},
+};
+use crate::model::audit::audit_model::{audit, AuditEntry, AuditEvent};
+use crate::model::profile::{
+    profile_model::{Permission, Profile},
+    profile_mutation::ProfileLoginOutput,
+    profile_repository::ProfileRepositoryT,
+};
+use crate::model::token::token_model::{audit_login, check_lockout, issue_token_pair};
+
+use super::{
+    two_factor_error::{
+        ERR_2FA__ALREADY_ENABLED, ERR_2FA__INVALID_CODE, ERR_2FA__NOT_ENABLED,
+        ERR_2FA__NOT_ENROLLED,
+    },
+    two_factor_model::{
+        generate_recovery_code, normalize_recovery_code, TwoFactor, TwoFactorEnrollment,
+        RECOVERY_CODES_COUNT,
+    },
+    two_factor_repository::TwoFactorRepositoryT,
+};
+
+#[derive(Default)]
+pub struct TwoFactorMutation;
+
+#[Object]
+impl<'a> TwoFactorMutation {
+    /// Метод подключения двухфакторной аутентификации.
+    ///
+    /// Возвращает секрет и ссылку `otpauth://` для приложения-аутентификатора.
+    /// Аутентификация включается только после подтверждения кодом.
+    #[graphql(guard = "AuthGuard::at_least(Permission::User).and(AuthGuard::session())")]
+    async fn enroll_two_factor(
+        &'a self,
+        ctx: &'a Context<'_>,
+    ) -> GraphQLResult<TwoFactorEnrollment> {
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let two_factor_service = ctx.data::<Arc<dyn TwoFactorRepositoryT>>()?;
+        let access_claims = get_access_claims(ctx);
+
+        if let Some(two_factor) = two_factor_service
+            .get(access_claims.sub().to_string())
+            .await?
+        {
+            if two_factor.enabled() {
+                return Err(crate::unprocessable!(
+                    "two_factor",
+                    Some(ERR_2FA__ALREADY_ENABLED.to_string())
+                )
+                .into());
+            }
+        }
+
+        let profile = profile_service
+            .get_data(access_claims.sub().to_string())
+            .await?;
+        let secret = totp::generate_secret();
+
+        two_factor_service
+            .enroll(access_claims.sub().to_string(), secret.clone())
+            .await?;
+
+        let otpauth_uri = totp::otpauth_uri(&secret, &profile.username);
+        Ok(TwoFactorEnrollment::new(secret, otpauth_uri))
+    }
+
+    /// Метод подтверждения подключения кодом из приложения.
+    ///
+    /// Возвращает одноразовые коды восстановления. Коды показываются
+    /// один раз, в графе хранятся только их argon2 хеши.
+    #[graphql(guard = "AuthGuard::at_least(Permission::User).and(AuthGuard::session())")]
+    async fn confirm_two_factor(
+        &'a self,
+        ctx: &'a Context<'_>,
+        code: String,
+    ) -> GraphQLResult<Vec<String>> {
+        let two_factor_service = ctx.data::<Arc<dyn TwoFactorRepositoryT>>()?;
+        let access_claims = get_access_claims(ctx);
+        let profile_id = access_claims.sub().to_string();
+
+        let two_factor = match two_factor_service.get(profile_id.clone()).await? {
+            Some(two_factor) if two_factor.enabled() => {
+                return Err(crate::unprocessable!(
+                    "two_factor",
+                    Some(ERR_2FA__ALREADY_ENABLED.to_string())
+                )
+                .into())
+            }
+            Some(two_factor) => two_factor,
+            None => {
+                return Err(crate::unprocessable!(
+                    "two_factor",
+                    Some(ERR_2FA__NOT_ENROLLED.to_string())
+                )
+                .into())
+            }
+        };
+
+        let step = match totp::verify(&two_factor.secret, &code, two_factor.last_step) {
+            Some(step) => step,
+            None => return Err(invalid_code().into()),
+        };
+
+        let codes = (0..RECOVERY_CODES_COUNT)
+            .map(|_| generate_recovery_code())
+            .collect::<Vec<String>>();
+        let hashes = codes
+            .iter()
+            .map(|code| Profile::hash_password(&normalize_recovery_code(code)))
+            .collect::<Result<Vec<String>, _>>()?;
+
+        two_factor_service
+            .enable(profile_id.clone(), hashes)
+            .await?;
+        two_factor_service
+            .set_last_step(profile_id.clone(), step)
+            .await?;
+
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::TwoFactorEnabled).target(&profile_id),
+        )
+        .await;
+
+        Ok(codes)
+    }
+
+    /// Метод отключения двухфакторной аутентификации.
+    /// Требует текущий пароль и код из приложения либо код восстановления.
+    /// Неверные пароли и коды учитываются так же, как при входе.
+    #[graphql(guard = "AuthGuard::at_least(Permission::User).and(AuthGuard::session())")]
+    async fn disable_two_factor(
+        &'a self,
+        ctx: &'a Context<'_>,
+        password: String,
+        code: String,
+    ) -> GraphQLResult<&str> {
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let two_factor_service = ctx.data::<Arc<dyn TwoFactorRepositoryT>>()?;
+        let lockout = ctx.data::<Arc<LoginLockout>>()?;
+        let ip = ctx.data_opt::<ClientInfo>().and_then(|client| client.ip());
+        let access_claims = get_access_claims(ctx);
+
+        check_lockout(ctx, Some(access_claims.sub())).await?;
+
+        let profile = profile_service
+            .get_data(access_claims.sub().to_string())
+            .await?;
+
+        if !profile.verify_password(&password)? {
+            lockout.failure(Some(access_claims.sub()), ip);
+            return Err(CustomError::new().kind(InvalidCredentials).build().into());
+        }
+
+        let two_factor = match two_factor_service.get(profile.id.to_string()).await? {
+            Some(two_factor) if two_factor.enabled() => two_factor,
+            _ => {
+                return Err(crate::unprocessable!(
+                    "two_factor",
+                    Some(ERR_2FA__NOT_ENABLED.to_string())
+                )
+                .into())
+            }
+        };
+
+        if !check_second_factor(ctx, &profile, &two_factor, &code).await? {
+            lockout.failure(Some(access_claims.sub()), ip);
+            return Err(invalid_code().into());
+        }
+
+        lockout.success(access_claims.sub());
+
+        two_factor_service.disable(profile.id.to_string()).await?;
+
+        audit(
+            ctx,
+            AuditEntry::new(AuditEvent::TwoFactorDisabled).target(&profile.id.to_string()),
+        )
+        .await;
+
+        Ok("OK")
+    }
+
+    /// Метод завершения входа с двухфакторной аутентификацией.
+    ///
+    /// Принимает токен, полученный от `login`, и код из приложения
+    /// либо код восстановления. Неверные коды учитываются так же,
+    /// как неверные пароли.
+    async fn login_second_factor(
+        &'a self,
+        ctx: &'a Context<'_>,
+        challenge_token: String,
+        code: String,
+    ) -> GraphQLResult<ProfileLoginOutput> {
+        let claims = ActionClaims::decode(&challenge_token, TokenPurpose::TwoFactorLogin)?;
+
+        let profile_service = ctx.data::<Arc<dyn ProfileRepositoryT>>()?;
+        let two_factor_service = ctx.data::<Arc<dyn TwoFactorRepositoryT>>()?;
+        let lockout = ctx.data::<Arc<LoginLockout>>()?;
+        let ip = ctx.data_opt::<ClientInfo>().and_then(|client| client.ip());
+
+        check_lockout(ctx, Some(claims.sub())).await?;
+
+        let profile = profile_service.get_data(claims.sub().to_string()).await?;
+
+        // Токен выпущен до смены пароля
+        if claims.data() != Some(profile.password_fingerprint().as_str()) {
+            return Err(CustomError::new().kind(TokenInvalid).build().into());
+        }
+
+        let two_factor = match two_factor_service.get(profile.id.to_string()).await? {
+            Some(two_factor) if two_factor.enabled() => two_factor,
+            _ => return Err(CustomError::new().kind(TokenInvalid).build().into()),
+        };
+
+        if !check_second_factor(ctx, &profile, &two_factor, &code).await? {
+            lockout.failure(Some(claims.sub()), ip);
+            audit(
+                ctx,
+                AuditEntry::new(AuditEvent::LoginFailed)
+                    .target(claims.sub())
+                    .details("Invalid second factor code"),
+            )
+            .await;
+            return Err(invalid_code().into());
+        }
+
+        lockout.success(claims.sub());
+
+        let tokens = issue_token_pair(ctx, &profile, Uuid::new_v4()).await?;
+        audit_login(ctx, &profile).await;
+
+        Ok(tokens)
+    }
+}
+
+/// Проверка кода второго фактора.
+///
+/// Принятый код TOTP запоминается по шагу, использованный
+/// код восстановления удаляется.
+async fn check_second_factor(
+    ctx: &Context<'_>,
+    profile: &Profile,
+    two_factor: &TwoFactor,
+    code: &str,
+) -> GraphQLResult<bool> {
+    let two_factor_service = ctx.data::<Arc<dyn TwoFactorRepositoryT>>()?;
+
+    if let Some(step) = totp::verify(&two_factor.secret, code, two_factor.last_step) {
+        two_factor_service
+            .set_last_step(profile.id.to_string(), step)
+            .await?;
+
+        return Ok(true);
+    }
+
+    let code = normalize_recovery_code(code);
+    for recovery_code in two_factor_service
+        .get_recovery_codes(profile.id.to_string())
+        .await?
+    {
+        if argon2::verify_encoded(&recovery_code.hash, code.as_bytes())? {
+            two_factor_service
+                .use_recovery_code(recovery_code.id)
+                .await?;
+
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+fn invalid_code<'a>() -> CustomError<'a> {
+    CustomError::new()
+        .kind(InvalidCredentials)
+        .details(&ERR_2FA__INVALID_CODE)
+        .build()
+}
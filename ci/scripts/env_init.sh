@@ -20,6 +20,21 @@ NEO4J_AUTH_HOST=$NEO4J_AUTH_HOST
 NEO4J_AUTH_PORT=$NEO4J_AUTH_PORT
 NEO4J_AUTH_USER=$NEO4J_AUTH_USER
 NEO4J_AUTH_PASSWORD=$NEO4J_AUTH_PASSWORD
+
+JWT_KEYS_PATH=$JWT_KEYS_PATH
+TOKEN_REVOCATION_STORE=$TOKEN_REVOCATION_STORE
+APP_URL=$APP_URL
+MAILER=$MAILER
+MAIL_FROM="$MAIL_FROM"
+MAIL_OUTBOX_DIR=$MAIL_OUTBOX_DIR
+SMTP_HOST=$SMTP_HOST
+SMTP_PORT=$SMTP_PORT
+SMTP_USER=$SMTP_USER
+SMTP_PASSWORD=$SMTP_PASSWORD
+OIDC_PROVIDERS_PATH=$OIDC_PROVIDERS_PATH
+BREACHED_PASSWORDS_PATH=$BREACHED_PASSWORDS_PATH
+LIMITS_PATH=$LIMITS_PATH
+ACCOUNT_DELETION_GRACE_DAYS=$ACCOUNT_DELETION_GRACE_DAYS
 EOF
 }
 
@@ -0,0 +1,23 @@
+#!/bin/bash
+
+# Генерация ключа подписи JWT для локального окружения.
+# Для ротации в jwt.json добавляется новый ключ и указывается
+# в поле `active`, старый ключ удаляется после истечения
+# срока действия выпущенных им токенов.
+KEYS_DIR=backend/keys
+
+if test ! -f "$KEYS_DIR/jwt.json"; then
+    mkdir -p "$KEYS_DIR"
+
+    KID=$(date +%Y-%m-%d)
+    openssl genpkey -algorithm ed25519 -out "$KEYS_DIR/$KID.pem"
+
+cat << EOF > "$KEYS_DIR/jwt.json"
+{
+    "active": "$KID",
+    "keys": [
+        { "kid": "$KID", "alg": "EdDSA", "private_key": "keys/$KID.pem" }
+    ]
+}
+EOF
+fi
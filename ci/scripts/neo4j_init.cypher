@@ -3,3 +3,42 @@ LOAD CSV WITH HEADERS FROM 'https://gist.githubusercontent.com/I0HuKc/69ae9817a3
 WITH row WHERE row.name IS NOT NULL
 MERGE (c:Language {code: row.name});
 
+
+// Refresh токены
+CREATE CONSTRAINT refresh_token_jti IF NOT EXISTS ON (t:RefreshToken) ASSERT t.jti IS UNIQUE;
+CREATE INDEX refresh_token_family IF NOT EXISTS FOR (t:RefreshToken) ON (t.family);
+
+// Отозванные Access токены
+CREATE CONSTRAINT revoked_token_jti IF NOT EXISTS ON (r:RevokedToken) ASSERT r.jti IS UNIQUE;
+
+// Сессии профилей
+CREATE CONSTRAINT session_id IF NOT EXISTS ON (s:Session) ASSERT s.id IS UNIQUE;
+
+// Коды восстановления двухфакторной аутентификации
+CREATE CONSTRAINT recovery_code_id IF NOT EXISTS ON (c:RecoveryCode) ASSERT c.id IS UNIQUE;
+
+// Учетные записи внешних провайдеров
+CREATE INDEX external_identity IF NOT EXISTS FOR (i:ExternalIdentity) ON (i.provider, i.subject);
+
+// API ключи
+CREATE CONSTRAINT api_key_id IF NOT EXISTS ON (k:ApiKey) ASSERT k.id IS UNIQUE;
+CREATE CONSTRAINT api_key_hash IF NOT EXISTS ON (k:ApiKey) ASSERT k.hash IS UNIQUE;
+
+// Журнал аудита
+CREATE CONSTRAINT audit_entry_id IF NOT EXISTS ON (a:AuditEntry) ASSERT a.id IS UNIQUE;
+CREATE INDEX audit_entry_created_at IF NOT EXISTS FOR (a:AuditEntry) ON (a.created_at);
+
+// Поиск профилей
+CREATE INDEX profile_username IF NOT EXISTS FOR (p:Profile) ON (p.username);
+CREATE INDEX profile_first_name IF NOT EXISTS FOR (p:Profile) ON (p.first_name);
+CREATE INDEX profile_age IF NOT EXISTS FOR (p:Profile) ON (p.age);
+CREATE INDEX language_code IF NOT EXISTS FOR (l:Language) ON (l.code);
+
+// Подписки: повторные связи, созданные до перехода на MERGE
+MATCH (a:Profile)-[r:SUBSCRIBE]->(b:Profile)
+WITH a, b, r ORDER BY r.timestamp
+WITH a, b, collect(r) AS rels WHERE size(rels) > 1
+FOREACH (r IN tail(rels) | DELETE r);
+
+// Рекомендации подписок: популярность записывается по идентификатору профиля
+CREATE INDEX profile_id IF NOT EXISTS FOR (p:Profile) ON (p.id);